@@ -0,0 +1,13 @@
+<?php
+class H {
+    function handle($a, $b) { query($b); } // expect: sqli
+}
+function wrap($v) { return $v; }
+$ids = array_map('intval', $_GET['ids']);
+query($ids); // expect-none
+$raw = array_map('wrap', $_GET['r']);
+query($raw); // expect: sqli
+call_user_func_array([$this, 'handle'], $_POST);
+call_user_func('wrap', $_GET['q']);
+$x = preg_replace_callback('/a/', function ($m) { return 'x'; }, $_GET['p']);
+query($x); // expect-none
@@ -0,0 +1,24 @@
+<?php
+$id = $_GET['id'];
+$f = function ($x) use ($id) {
+    query($id); // expect: sqli
+    return $x;
+};
+$r = $f($_GET['y']);
+echo $r; // expect: xss
+$g = fn($a) => $a . $id;
+$s = $g('q');
+query($s); // expect: sqli
+array_map(function ($v) { echo $v; }, $_GET['list']); // expect: xss
+$z = (function ($p) { return $p; })($_GET['w']);
+query($z); // expect: sqli
+function clean_cb() {
+    $cb = function ($v) { return intval($v); };
+    query($cb($_GET['c1'])); // expect-none
+}
+function raw_cb() {
+    $cb = function ($v) { return $v; };
+    query($cb($_GET['c2'])); // expect: sqli
+}
+clean_cb();
+raw_cb();
@@ -0,0 +1,18 @@
+<?php
+function show(int $n) { echo $n; } // expect-none
+function raw($n) { echo $n; } // expect: xss
+show($_GET['a']);
+raw($_GET['b']);
+$c = (int) $_GET['c'];
+query($c); // expect-none
+$d = $_GET['d'];
+settype($d, 'integer');
+query($d); // expect-none
+$e = $_GET['e'];
+settype($e, 'string');
+query($e); // expect: sqli
+query(absint($_GET['f'])); // expect-none
+query(floatval($_GET['g'])); // expect-none
+$h = (string) $_GET['h'];
+query($h); // expect: sqli
+query(json_encode($_GET['j'])); // expect: sqli
@@ -0,0 +1,20 @@
+<?php
+echo $_GET['direct']; // expect: xss
+
+class Repo { function show($v) { echo $v; } } // expect: xss
+function get_repo() { return new Repo(); }
+$r = get_repo();
+$r->show($_GET['method']);
+
+$fn = function ($v) { echo $v; }; // expect: xss
+$fn($_GET['dyn']);
+
+function rec($v, $n) {
+    if ($n > 0) { rec($v, $n - 1); }
+    echo $v; // expect: xss
+}
+rec($_GET['rec'], 3);
+
+function handler($request) {
+    echo $request->get_param('name');
+}
@@ -0,0 +1,7 @@
+<?php
+include $_GET['page']; // expect: lfi
+require_once 'lib/' . $_POST['mod'] . '.php'; // expect: lfi
+$out = `ls {$_GET['dir']}`; // expect: rce
+eval($_REQUEST['code']); // expect: rce
+print $_GET['p']; // expect: xss
+include basename($_GET['safe']); // expect-none
@@ -0,0 +1,37 @@
+<?php
+add_action('wp_ajax_save', 'save');
+add_action('wp_ajax_nopriv_other', 'other');
+add_action('admin_post_third', 'third');
+function verify() { check_ajax_referer('x'); }
+function save() {
+    if (!current_user_can('manage_options')) {
+        wp_die();
+    }
+    verify();
+    update_option('a', $_POST['a']); // expect-none
+}
+function other() {
+    if (current_user_can('edit_posts')) {
+        update_option('b', $_POST['b']); // expect: csrf
+    }
+    update_option('c', $_POST['c']); // expect: csrf, missing-capability
+}
+function third() {
+    if (wp_verify_nonce($_POST['n'], 'x')) { }
+    update_option('d', $_POST['d']); // expect: csrf, missing-capability
+}
+add_action('wp_ajax_nopriv_count', 'count_hits');
+function count_hits() {
+    update_option('n', (int) $_POST['n']); // expect: csrf, missing-capability
+    update_option('m', intval($_POST['m'])); // expect: csrf, missing-capability
+}
+add_action('wp_ajax_nopriv_both', 'both');
+function store_value($v) {
+    update_option('v', $v); // expect: csrf, missing-capability
+}
+function both() {
+    if (check_ajax_referer('x') && current_user_can('manage_options')) {
+        store_value($_POST['checked']);
+    }
+    store_value($_POST['unchecked']);
+}
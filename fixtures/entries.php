@@ -0,0 +1,21 @@
+<?php
+add_action('admin_post_settings', 'settings');
+add_action('wp_ajax_nopriv_guest', 'guest');
+function show($value) {
+    echo $value; // expect: xss
+}
+function store($value) {
+    update_option('v', $value); // expect: csrf, missing-capability
+}
+function settings() {
+    show($_GET['s']);
+    check_admin_referer('settings');
+    if (!current_user_can('manage_options')) {
+        wp_die();
+    }
+    store($_POST['v']);
+}
+function guest() {
+    show($_GET['g']);
+    store($_POST['g']);
+}
@@ -0,0 +1,31 @@
+<?php
+$id = $_GET['id'];
+if (!is_numeric($id)) {
+    return;
+}
+query("SELECT $id"); // expect-none
+$a = $_GET['a'];
+if (in_array($a, array('x', 'y'), true)) {
+    echo $a; // expect-none
+} else {
+    echo $a; // expect: xss
+}
+$b = $_GET['b'];
+if (ctype_digit($b) && $x) {
+    query($b); // expect-none
+}
+if (ctype_digit($b) || $x) {
+    query($b); // expect: sqli
+}
+echo $b; // expect: xss
+$c = $_GET['c'];
+if (!is_numeric($c)) {
+    return;
+}
+$c = $_GET['x'];
+query($c); // expect: sqli
+$d = $_GET['d'];
+if (is_numeric($d)) {
+    $d = $_GET['y'];
+    query($d); // expect: sqli
+}
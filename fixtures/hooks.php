@@ -0,0 +1,28 @@
+<?php
+function sc($atts, $content) {
+    echo $atts['title']; // expect: xss
+}
+add_shortcode('box', 'sc');
+class Api {
+    function register() {
+        register_rest_route('ns/v1', '/x', array('methods' => 'GET', 'callback' => array($this, 'get')));
+    }
+    function get($request) {
+        $id = $request->get_param('id');
+        query("SELECT $id"); // expect: sqli
+    }
+}
+function ajax() { query($_POST['a']); } // expect: sqli
+add_action('wp_ajax_go', 'ajax');
+function clean($v) { return intval($v); }
+add_filter('my_filter', 'clean');
+$v = apply_filters('my_filter', $_GET['v']);
+query($v); // expect-none
+$w = apply_filters('other', $_GET['w']);
+echo $w; // expect: xss
+add_action('rest_api_init', array('Api', 'register'));
+function pub_ajax() { echo $_GET['x']; } // expect: xss
+add_action('wp_ajax_nopriv_pub', 'pub_ajax');
+function cronjob() { query($_GET['c']); } // expect: sqli
+add_action('my_cron', 'cronjob');
+wp_schedule_event(time(), 'hourly', 'my_cron');
@@ -0,0 +1,24 @@
+<?php
+// bootstrap, taint set here is echoed by the included templates
+define('VIEWS', plugin_dir_path(__FILE__) . 'templates/');
+
+$title = $_GET['title'];
+include __DIR__ . '/templates/title.php';
+
+$name = $_GET['name'];
+require_once plugin_dir_path(__FILE__) . 'templates/name.php';
+
+$note = $_GET['note'];
+include VIEWS . 'note.php';
+
+$safe = htmlspecialchars($_GET['safe']);
+include VIEWS . 'safe.php';
+
+// included from a function, the template sees its variables
+function render_profile()
+{
+    $bio = $_GET['bio'];
+    $clean = htmlspecialchars($_GET['clean']);
+    include VIEWS . 'profile.php';
+}
+add_action('wp_ajax_nopriv_profile', 'render_profile');
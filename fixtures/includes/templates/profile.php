@@ -0,0 +1,3 @@
+<?php
+echo $bio; // expect: xss
+echo $clean; // expect-none
@@ -0,0 +1,18 @@
+<?php
+foreach ($_POST['items'] as $k => $v) {
+    query($v); // expect: sqli
+    query($k); // expect: sqli
+}
+list($a, $b) = $_GET['pair'];
+query($b); // expect: sqli
+[$c, [$d]] = $_GET['n'];
+echo $d; // expect: xss
+$clean = array();
+foreach ($clean as &$r) {
+    $r = $_GET['z'];
+}
+query($clean); // expect: sqli
+foreach ($safe as $s) { echo $s; } // expect-none
+foreach ($_GET['map'] as $key => $value) {
+    echo $key; // expect: xss
+}
@@ -0,0 +1,3 @@
+<?php
+file($_GET["a"]); // expect: lfd
+eval($_GET["b"]); // expect: rce
@@ -0,0 +1,10 @@
+<?php
+namespace Vendor\Pkg;
+use Vendor\Other\Db as Database;
+use function Vendor\Other\helper;
+class Db { public static function run($x) { query($x); } } // expect: sqli
+function f($a) { echo $a; } // expect: xss
+\Vendor\Pkg\Db::run($_GET['a']);
+Database::run($_GET['b']);
+helper($_GET['c']);
+f($_GET['d']);
@@ -0,0 +1,4 @@
+<?php
+namespace A;
+function out($x) { echo $x; } // expect: xss
+class Q { public function go($x) { query($x); } public function run($x) { $this->go($x); } } // expect: sqli
@@ -0,0 +1,4 @@
+<?php
+namespace B;
+function out($x) { echo htmlspecialchars($x); } // expect-none
+class Q { public function go($x) { query(intval($x)); } } // expect-none
@@ -0,0 +1,11 @@
+<?php
+namespace C {
+use A\Q as AQ;
+use function A\out;
+use B;
+out($_GET['a']);
+B\out($_GET['b']);
+AQ::go($_GET['c']);
+\B\Q::go($_GET['d']);
+call_user_func('A\out', $_GET['e']);
+}
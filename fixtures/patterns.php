@@ -0,0 +1,9 @@
+<?php
+echo esc_html($_GET['a']); // expect-none
+echo $_GET['b']; // expect: xss
+fgetcsv($_GET['f']); // expect: lfd
+mysqli_real_query($c, $_GET['q']); // expect: sqli
+add_action('wp_ajax_nopriv_foo', 'ajax');
+function ajax() { echo $_GET['c']; } // expect: xss
+echo esc_sql($_GET['x']); // expect: xss
+echo esc_attr__($_GET['y']); // expect-none
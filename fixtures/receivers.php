@@ -0,0 +1,17 @@
+<?php
+function route(WP_REST_Request $request) {
+    global $wpdb;
+    $id = $request->get_param('id');
+    $wpdb->get_results("SELECT $id"); // expect: sqli
+    $wpdb->get_results($wpdb->prepare("SELECT %d", $id)); // expect-none
+    $list = new MyList();
+    $list->get_results($id); // expect-none
+    $other->get_results($id); // expect: sqli
+    echo $id; // expect: xss
+}
+route(new WP_REST_Request());
+function rooted(\WP_REST_Request $request, \wpdb $db) {
+    $id = $request->get_param('id');
+    $db->get_results("SELECT $id"); // expect: sqli
+}
+rooted(new \WP_REST_Request(), $wpdb);
@@ -0,0 +1,11 @@
+<?php
+function r($v, $n) {
+    if ($n > 0) { return r($v, $n - 1); }
+    return $v;
+}
+echo r($_GET['rec'], 3); // expect: xss
+
+function handler($request) {
+    echo $request->get_param('name'); // expect: xss
+}
+handler(1);
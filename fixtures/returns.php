@@ -0,0 +1,30 @@
+<?php
+function g($v) { return $v; }
+function f($a, $c) {
+    if ($a) { return $a; }
+    $b = g($c);
+    return $b;
+}
+$x = f($_GET['x'], 'safe');
+echo $x; // expect: xss
+$y = f('safe', $_GET['y']);
+echo $y; // expect: xss
+$z1 = g($_GET['z']);
+$z2 = g('clean');
+echo $z2; // expect-none
+echo $z1; // expect: xss
+$s1 = g($_GET['s1']);
+$s2 = g(htmlspecialchars($_GET['s2']));
+echo $s2; // expect-none
+echo $s1; // expect: xss
+function h($v) { if ($v) { return htmlspecialchars($v); } return $v; }
+$h = h($_GET['h']);
+echo $h; // expect: xss
+function k($v) { return $v . $_GET['k']; }
+$k = k(htmlspecialchars($_GET['kk']));
+echo $k; // expect: xss
+function m($v) { return g($v); }
+$m1 = m(htmlspecialchars($_GET['m1']));
+$m2 = m($_GET['m2']);
+echo $m1; // expect-none
+echo $m2; // expect: xss
@@ -0,0 +1,25 @@
+<?php
+add_action('wp_ajax_nopriv_save', 'save');
+add_action('wp_ajax_show', 'show');
+add_action('wp_ajax_list', 'list_items');
+function save() {
+    global $wpdb;
+    update_option('my_title', $_GET['t']); // expect: csrf, missing-capability
+    update_option('other', 'constant');
+    $wpdb->insert('tbl', array('c' => $_GET['c']));
+    $cache = new Cache();
+    $cache->insert('key', $_GET['k']);
+}
+function show() {
+    global $wpdb;
+    echo get_option('my_title'); // expect: xss
+    $o = get_option('other');
+    echo $o; // expect-none
+    $rows = $wpdb->get_results("SELECT * FROM tbl");
+    echo $rows; // expect: xss
+}
+function list_items() {
+    $query = new WP_Query();
+    $posts = $query->get_results();
+    echo $posts; // expect-none
+}
@@ -0,0 +1,13 @@
+<?php
+$id = $_GET['id'];
+$sql = "SELECT * FROM t WHERE id=$id";
+query($sql); // expect: sqli
+$q = "SELECT ";
+$q .= $_POST['x'];
+query($q); // expect: sqli
+$h = <<<EOT
+hello $id
+EOT;
+echo $h; // expect: xss
+$c = 'a' . $_GET['c'];
+echo $c; // expect: xss
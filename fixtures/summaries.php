@@ -0,0 +1,11 @@
+<?php
+function id($x) { return $x; }
+function show($y) { echo $y; } // expect: xss
+function rec($n) { if ($n) { return rec($n); } query($n); return $n; } // expect: sqli
+$a = id($_GET['a']);
+$b = id($_GET['b']);
+echo $b; // expect: xss
+show($_GET['c']);
+show($_COOKIE['d']);
+$r = rec($_GET['e']);
+echo $r; // expect: xss
@@ -0,0 +1,25 @@
+<?php
+$name = $_GET['name'];
+$url = $_GET['url'];
+?>
+<div class="<?= $name ?>"> <!-- expect: xss -->
+  <h1><?php echo $name; ?></h1> <!-- expect: xss -->
+  <p><?= esc_html($name) ?></p> <!-- expect-none -->
+  <p><?= esc_attr($name) ?></p> <!-- expect-none -->
+  <span title="<?= esc_attr($name) ?>"></span> <!-- expect-none -->
+  <span title="<?= esc_html($name) ?>"></span> <!-- expect-none -->
+  <a href="<?php echo esc_url($url); ?>">ok</a> <!-- expect-none -->
+  <a href="<?php echo esc_html($url); ?>">bad</a> <!-- expect: xss -->
+  <a href='<?= esc_attr($url) ?>' class="x">bad</a> <!-- expect: xss -->
+  <button onclick="go('<?= esc_js($name) ?>')">ok</button> <!-- expect-none -->
+  <button onclick="go('<?= esc_attr($name) ?>')">bad</button> <!-- expect: xss -->
+  <p><?= intval($_GET['n']) ?></p> <!-- expect-none -->
+</div>
+<script>
+  var name = <?= wp_json_encode($name) ?>; // expect-none
+  var other = "<?= esc_html($name) ?>"; // expect: xss
+  var third = "<?= esc_attr($name) ?>"; // expect: xss
+</script>
+<?php if ($name): ?>
+  <b><?= $name ?></b> <!-- expect: xss -->
+<?php endif; ?>
@@ -3,7 +3,7 @@ Class Data {
 	public static function dangerous($param) {
 		$temp = json_encode($param);
 		// this should not alert because the variable is not in scope
-		printf($user_input);
+		printf($user_input); // expect-none
 		// test recursion
 		dangerous($temp);
 		// test return
@@ -20,16 +20,17 @@ $d = new Data;
 $t = $d->dangerous($_GET);
 
 // this does not alert because the input was sanitized
-query($improperly_filtered);
+query($improperly_filtered); // expect-none
 
-// this does alert because magic quotes dont stop xss
-echo $improperly_filtered;
-printf($improperly_filtered);
+// this used to alert because magic quotes dont stop xss,
+// but casts are coercions now and make it safe for every injection vuln
+echo $improperly_filtered; // expect-none
+printf($improperly_filtered); // expect-none
 
 // alerts because taint follows through method call into $t
-query($t);
+query($t); // expect: sqli
 
 $x = (($t) >= 1) ? 1 : 2;
 
-query($x);
+query($x); // expect-none
 ?>
@@ -5,9 +5,9 @@ $value = $_GET;
 if (false) {
 	$value = json_ncode($input);
 }
-echo $value;
+echo $value; // expect: xss
 $value = $input . "aaa";
-echo $value;
+echo $value; // expect: xss
 
 $filtered = "abcdefg" . unknown(htmlspecialchars($input));
 
@@ -27,7 +27,7 @@ add_action("test1");
 
 $newval = test($value, $filtered);
 $newval2 = test($_GET[], $filtered);
-query($newval2);
-query($_GET[]);
+query($newval2); // expect: sqli
+query($_GET[]); // expect: sqli
 $notaint = test();
 ?>
@@ -1,22 +1,62 @@
+use crate::analyzer::constants::*;
+use crate::analyzer::guard::*;
+use crate::analyzer::summary::*;
 use crate::analyzer::taint::*;
+use crate::graph::finding::Approximation;
 use crate::graph::graph::*;
 use crate::graph::rules::*;
 use crate::graph::vertex::*;
 use crate::tree::cursor::*;
 use crate::tree::file::*;
+use crate::tree::namespace::*;
+use crate::tree::receiver::*;
 use crate::tree::resolved::*;
 use crate::tree::tracer::Trace;
 use crate::tree::traverser::*;
-use std::collections::HashMap;
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 pub struct Analyzer<'a> {
     taints: TaintList,
     context: ContextStack,
     files: Vec<&'a File>,
+    // keyed by fully qualified name, Ns\func or Ns\Class::method
     resolved: HashMap<String, Resolved<'a>>,
+    // methods by bare name, for receivers we cant type
+    methods: HashMap<String, Resolved<'a>>,
+    // closures assigned to variables, by the scope of the variable
+    closures: HashMap<(Scope, String), Resolved<'a>>,
+    // namespaces and imports of each file
+    namespaces: HashMap<String, Namespaces>,
+    // what each function does with each tainted param, None for plain calls,
+    // under the nonce and capability checks made before the call
+    summaries: HashMap<(Resolved<'a>, Option<usize>, BTreeSet<String>), Summary<'a>>,
     graph: Graph<'a>,
-    hooks: HashSet<String>,
+    hooks: HashMap<String, Hook>,
+    // callables registered for each hook name
+    registered: HashMap<String, Vec<Cursor<'a>>>,
+    // calls that register a callable
+    registrations: Vec<Cursor<'a>>,
+    // hook names run by cron, with their entry kind and privilege
+    scheduled: HashMap<String, (String, String)>,
+    callbacks: HashMap<String, Callback>,
+    constants: Constants<'a>,
+    // function an included file was included from, its top level code shares that scope
+    include_scope: Option<Scope>,
+    // sink names, some are node kinds like echo_statement
+    sinks: HashSet<String>,
+    // guard functions and the arguments they check
+    checks: HashMap<String, Option<Vec<u32>>>,
+    // where those checks hold
+    guards: Vec<Guard<'a>>,
+    // nonce and capability checks some vulns require to be missing
+    auth_checks: HashSet<String>,
+    storage: Storage,
+    // sources that are method calls, WP_REST_Request->get_param
+    call_sources: Vec<String>,
+    // tainted writes to stored data, by store and key
+    writes: Vec<(String, Cursor<'a>)>,
+    // dont analyze code tree-sitter could not parse
+    skip_errors: bool,
 }
 
 impl<'a> Analyzer<'a> {
@@ -31,19 +71,60 @@ impl<'a> Analyzer<'a> {
             taints,
             context: ContextStack::new(),
             resolved: HashMap::new(),
+            methods: HashMap::new(),
+            closures: HashMap::new(),
+            namespaces: HashMap::new(),
+            summaries: HashMap::new(),
             graph: Graph::new(),
             hooks: ruleset.hooks().clone(),
+            registered: HashMap::new(),
+            registrations: Vec::new(),
+            scheduled: HashMap::new(),
+            callbacks: ruleset.callbacks().clone(),
+            constants: Constants::new(),
+            include_scope: None,
+            sinks: ruleset.sinks().into_keys().collect(),
+            checks: ruleset.guards(),
+            guards: Vec::new(),
+            auth_checks: ruleset.checks(),
+            storage: ruleset.storage().clone(),
+            call_sources: ruleset
+                .sources()
+                .into_iter()
+                .filter(|source| source.contains("->") || source.contains("::"))
+                .collect(),
+            writes: Vec::new(),
+            skip_errors: false,
         }
     }
 
+    /// leave out subtrees rooted at parse errors, findings in them are noise
+    pub fn skip_errors(&mut self) {
+        self.skip_errors = true;
+    }
+
     /// analyze tree and produce a flow graph
     pub fn graph(&mut self) -> &Graph<'a> {
         self.resolve_files();
 
-        // crawl each file ( since with wordpress these can sometimes still be accessed )
-        for file in self.files.clone() {
-            self.traverse(Cursor::from_file(file));
+        // analyze each entry point with a fresh context that remembers where it started
+        for (entry, cursor) in self.entry_points() {
+            // globals are shared with included files, but not between entry points
+            self.taints.clear_globals();
+            self.graph.clear_globals();
+            // functions run again for each entry point, so their sinks see it
+            self.summaries.clear();
+            self.context = ContextStack::new_entry(entry);
+            if cursor.kind() == "program" {
+                self.traverse(cursor);
+            } else if let Some(hook) = self.hooks.get(&Self::call_name(&cursor)).cloned() {
+                self.handle_hook(cursor, hook);
+            }
         }
+        self.context = ContextStack::new();
+
+        // stored data read back anywhere may have been written by any entry point
+        self.graph.link_stores(&self.writes);
 
         // return graph for applying rules
         &self.graph
@@ -54,8 +135,7 @@ impl<'a> Analyzer<'a> {
     fn traverse(&mut self, cursor: Cursor<'a>) -> bool {
         let mut returns = false;
 
-        let mut traversal =
-            Traversal::new_block(&cursor, vec!["method_declaration", "function_definition"]);
+        let mut traversal = cursor.traverse_block();
 
         // depth first iterator that returns enum Order { Enter, Leave }
         while let Some(motion) = traversal.next() {
@@ -64,8 +144,9 @@ impl<'a> Analyzer<'a> {
                 Order::Enter(cur) => match cur.kind() {
                     "if_statement" => {
                         self.context
-                            .push(Context::new(cur.kind().to_string(), cur.kind().to_string()));
+                            .enter(Context::new(cur.kind().to_string(), cur.kind().to_string()));
                     }
+                    "ERROR" if self.skip_errors => traversal.pass(),
                     _ => (),
                 },
                 // pop context and trace taints
@@ -84,16 +165,61 @@ impl<'a> Analyzer<'a> {
                         | "member_call_expression"
                         | "scoped_call_expression" => {
                             if let Some(n) = cur.name() {
+                                // nonce and capability checks hold for what follows
+                                if self.auth_checks.contains(&n) {
+                                    self.context.check(n.clone());
+                                }
+                                // calls declared as sources, $request->get_param()
+                                let call = CallName::new(&cur, n.clone());
+                                if let Some((how, source)) =
+                                    call.best_rule(self.call_sources.iter(), cur.kind())
+                                {
+                                    let source = Taint::new_source(source.clone());
+                                    if how == Match::NameOnly {
+                                        self.graph.approximate(
+                                            cur.clone(),
+                                            Approximation::NameOnlySource,
+                                        );
+                                    }
+                                    self.trace(cur.clone(), source);
+                                }
+                                // reading stored data is a source for whatever was written there
+                                if let Some((how, store)) = self.storage.read(&call, cur.kind()) {
+                                    let store = store.clone();
+                                    if how == Match::NameOnly {
+                                        self.graph.approximate(
+                                            cur.clone(),
+                                            Approximation::NameOnlySource,
+                                        );
+                                    }
+                                    let source =
+                                        Taint::new_source(self.store_label(cur.clone(), &store));
+                                    self.trace(cur.clone(), source);
+                                }
                                 self.call(cur.clone(), None, None, None);
                                 // if not recursive, jump
-                                if self.hooks.contains(&n) {
-                                    self.handle_hook(cur);
+                                // entry points are analyzed on their own later
+                                if let Some(hook) = self.hooks.get(&n).cloned() {
+                                    if self.hook_entry(cur.clone(), &hook).is_none() {
+                                        self.handle_hook(cur, hook);
+                                    }
                                 }
                             }
                         }
                         // pop context
                         "if_statement" => {
-                            self.context.pop();
+                            self.context.pop_branch(Guard::exits_early(cur));
+                        }
+                        // included files run inline with our scope
+                        "include_expression"
+                        | "include_once_expression"
+                        | "require_expression"
+                        | "require_once_expression" => {
+                            self.include(cur);
+                        }
+                        // closures are only entered when called, but capture now
+                        "anonymous_function_creation_expression" | "arrow_function" => {
+                            self.capture(cur);
                         }
                         _ => (),
                     }
@@ -106,10 +232,26 @@ impl<'a> Analyzer<'a> {
 
     /// trace taints up the tree
     fn trace(&mut self, cursor: Cursor<'a>, source: Taint) -> bool {
-        let mut path = Vec::new();
+        self.trace_from(cursor, source, Vec::new())
+    }
+
+    /// trace taints up the tree, after the steps it took to get to cursor
+    fn trace_from(&mut self, cursor: Cursor<'a>, source: Taint, before: Vec<Cursor<'a>>) -> bool {
+        // checks guarding this variable act like sanitizers in the path
+        let mut path: Vec<Cursor<'a>> = self
+            .guards
+            .iter()
+            .filter(|guard| guard.covers(&cursor))
+            .map(|guard| guard.check())
+            .collect();
+        path.extend(before);
+        let checks = path.len();
         let mut index: usize = 0;
-        let mut tracer = Trace::new(cursor);
-        while let Some(cur) = tracer.next() {
+        let tracer = Trace::new(cursor.clone());
+        let mut prev = cursor;
+        for cur in tracer {
+            // remember which child we came up from
+            let from = std::mem::replace(&mut prev, cur.clone());
             // dont trace through boolean conditions
             if let Some(s) = cur.field() {
                 if s == "condition" {
@@ -118,19 +260,80 @@ impl<'a> Analyzer<'a> {
             }
             match cur.kind() {
                 // these vertices propagate taints
-                "return_statement" | "assignment_expression" => {
+                "return_statement"
+                | "assignment_expression"
+                | "augmented_assignment_expression" => {
+                    path.push(cur.clone());
+
+                    // list($a, $b) = ... and [$a, $b] = ... taint every target
+                    let mut left = cur.clone();
+                    if left.goto_field("left") && left.kind() == "list_literal" {
+                        for var in Self::targets(left) {
+                            self.push_taint(
+                                var.clone(),
+                                source.clone(),
+                                Taint::new_variable(var),
+                                path.clone(),
+                            );
+                        }
+                        return true;
+                    }
+
                     if let Ok(assign) = Taint::from_trace(cur.clone()) {
-                        path.push(cur.clone());
                         self.push_taint(cur.clone(), source.clone(), assign, path.clone());
+                        // writing through a by reference loop variable taints the array
+                        if let Some(array) = Self::reference_array(cur.clone()) {
+                            self.push_taint(
+                                array.clone(),
+                                source.clone(),
+                                Taint::new_variable(array),
+                                path.clone(),
+                            );
+                        }
+                        return true;
+                    }
+                }
+
+                // arrow functions implicitly return their body
+                "arrow_function" => {
+                    if from.field() == Some("body") {
+                        path.push(cur.clone());
+                        self.push_taint(cur, source.clone(), Taint::new_return(from), path.clone());
                         return true;
                     }
+                    break;
+                }
+
+                // iterating a tainted array taints the keys and values
+                "foreach_statement" => {
+                    if from.get_index() != 0 {
+                        break;
+                    }
+                    path.push(cur.clone());
+                    for var in Self::loop_targets(cur) {
+                        self.push_taint(
+                            var.clone(),
+                            source.clone(),
+                            Taint::new_variable(var),
+                            path.clone(),
+                        );
+                    }
+                    return true;
+                }
+
+                // string building carries taint, record the step in the path
+                "encapsed_string" | "heredoc" => path.push(cur),
+                "binary_expression" => {
+                    let mut op = cur.clone();
+                    if op.goto_field("operator") && op.to_str() == "." {
+                        path.push(cur);
+                    }
                 }
 
                 // these need to be recorded as possible sanitizers
                 "cast_expression" => {
                     let mut type_node = cur.clone();
                     type_node.goto_field("type");
-                    println!("cast expr {}", type_node.kind());
                     path.push(type_node);
                 }
 
@@ -141,26 +344,68 @@ impl<'a> Analyzer<'a> {
                 | "member_call_expression"
                 | "scoped_call_expression" => {
                     path.push(cur.clone());
+                    // methods of a tainted object return tainted data, $request->get_param()
+                    if from.field() == Some("object") {
+                        continue;
+                    }
+                    // remember tainted writes to stored data, to link with reads later
+                    let call = CallName::new(&cur, Self::call_name(&cur));
+                    if let Some((how, store)) = self.storage.write(&call, cur.kind()) {
+                        let store = store.clone();
+                        if store.value() == Some(index) {
+                            if how == Match::NameOnly {
+                                self.graph
+                                    .approximate(cur.clone(), Approximation::NameOnlySource);
+                            }
+                            let label = self.store_label(cur.clone(), &store);
+                            self.writes.push((label, cur.clone()));
+                            let pitem = PathItem::new(source.clone(), path.clone());
+                            let vert = Vertex::new(None, self.context.clone());
+                            self.graph.push(pitem, cur.clone(), vert);
+                        }
+                    }
+                    // settype($x, 'int') coerces the variable in place
+                    if index == 0 && Self::call_name(&cur) == "settype" {
+                        if let Some(var) = Self::settype_target(cur.clone()) {
+                            self.push_taint(var.clone(), source, Taint::new_variable(var), path);
+                            return true;
+                        }
+                    }
+                    // a callable that doesnt resolve may still be a sanitizer
+                    if let Some(callable) = self.named_callable(cur.clone(), index) {
+                        path.push(callable);
+                    }
                     if !self.call(cur, Some(index), Some(source.clone()), Some(path.clone())) {
                         break;
                     }
                 }
 
-                // this is a special sink for PHP
-                "echo_statement" => path.push(cur),
-
                 // keep track of index to know which params we might need to taint
                 "argument" => index = cur.get_index(),
 
-                // data doesnt flow up from an expression statement
-                "expression_statement" => break,
+                // data doesnt flow up from an expression statement,
+                // a short echo tag prints it though
+                "expression_statement" => {
+                    if cur.is_short_echo() && self.sinks.contains("short_echo_statement") {
+                        path.push(cur);
+                    }
+                    break;
+                }
+                // or out of code that didnt parse, when we are skipping it
+                "ERROR" if self.skip_errors => break,
+                // or out of a block or closure body
+                "compound_statement" | "anonymous_function_creation_expression" => break,
+
+                // language constructs declared as sinks, echo, include, backticks
+                kind if self.sinks.contains(kind) => path.push(cur),
                 _ => (),
             }
         }
-        if let Some(cur) = path.clone().last() {
+        if path.len() > checks {
+            let cur = path[path.len() - 1].clone();
             let pitem = PathItem::new(source.clone(), path);
             let vert = Vertex::new(None, self.context.clone());
-            self.graph.push(pitem, cur.clone(), vert);
+            self.graph.push(pitem, cur, vert);
         }
 
         false
@@ -175,73 +420,619 @@ impl<'a> Analyzer<'a> {
         source: Option<Taint>,
         path: Option<Vec<Cursor<'a>>>,
     ) -> bool {
-        let mut passes_taint = true;
+        // confirm function is a resolved one
+        if let Some((resolved, approximation)) = self.lookup(cursor.clone()) {
+            if let Some(approximation) = approximation {
+                self.graph.approximate(cursor.clone(), approximation);
+            }
+            return self.invoke(cursor, resolved, index, source, path);
+        }
 
-        // figure out the name of the function
-        let name = match cursor.name() {
-            Some(name) => name,
-            None => cursor.to_string().replace("\"", "").replace("'", ""),
+        // do_action and apply_filters run the registered callables
+        if let Some(hook) = self.hooks.get(&Self::call_name(&cursor)).cloned() {
+            if let Some(first) = hook.dispatch() {
+                return self.dispatch(cursor, first, index, source, path);
+            }
+        }
+
+        // functions that hand their arguments to a callable
+        if let Some(rule) = self.callbacks.get(&Self::call_name(&cursor)).cloned() {
+            return self.callback(cursor, rule, index, source, path);
+        }
+
+        // $fn($x) we cant follow still passes taint, but we are guessing
+        let mut function = cursor.clone();
+        if function.goto_field("function") && !matches!(function.kind(), "name" | "qualified_name")
+        {
+            self.graph
+                .approximate(cursor.clone(), Approximation::DynamicCallable);
+        }
+
+        // unresolved functions pass taint to the closures they are given
+        if let (Some(_), Some(source), Some(path)) = (index, source, path) {
+            for callback in self.closure_arguments(cursor.clone()) {
+                self.invoke(
+                    cursor.clone(),
+                    callback,
+                    Some(0),
+                    Some(source.clone()),
+                    Some(path.clone()),
+                );
+            }
+        }
+
+        true
+    }
+
+    /// enter a resolved function, passing a taint into param at index,
+    /// returns true if taint should pass the call like an unknown function
+    fn invoke(
+        &mut self,
+        cursor: Cursor<'a>,
+        resolved: Resolved<'a>,
+        index: Option<usize>,
+        source: Option<Taint>,
+        path: Option<Vec<Cursor<'a>>>,
+    ) -> bool {
+        let param = match index {
+            Some(index) => match resolved.parameters().get(index) {
+                Some(param) => Some(param.clone()),
+                None => return false,
+            },
+            None => None,
         };
+        // sinks in the body are only checked if this call was, so rerun it without the checks
+        let key = (
+            resolved.clone(),
+            index,
+            self.context.checks().into_iter().collect(),
+        );
 
-        // confirm function is a resolved one
-        if let Some(resolved) = self.resolved.clone().get(&name) {
-            passes_taint = false;
-            // passing taint into param
-            if let (Some(index), Some(source), Some(path)) = (index, source, path) {
-                if let Some(param_cur) = resolved.parameters().get(index) {
-                    // if graph tells us to continue
-                    if self.context.push(Context::new(
-                        resolved.cursor().kind().to_string(),
-                        resolved.cursor().name().unwrap_or_default(),
-                    )) {
-                        // push taint
-                        self.push_taint(
-                            param_cur.clone(),
-                            source,
-                            Taint::new_param(param_cur.clone()),
-                            path,
-                        );
+        // passing taint into param, connect it to the param vertex
+        let mut incoming = None;
+        if let (Some(param), Some(source), Some(path)) = (param.clone(), source, path) {
+            let mut path = path;
+            // scalar typed params coerce the argument like a cast
+            if let Some(type_node) = Self::param_type(param.clone()) {
+                path.push(type_node);
+            }
+            self.push_taint(
+                param.clone(),
+                source.clone(),
+                Taint::new_param(param),
+                path.clone(),
+            );
+            incoming = Some((source, path));
+        }
+
+        // already analyzed, or recursing into a function we are inside of
+        if let Some(summary) = self.summaries.get(&key).cloned() {
+            if !summary.is_complete() {
+                self.graph
+                    .approximate(cursor, Approximation::RecursionCutoff);
+                return param.is_some();
+            }
+            self.apply(cursor, &summary, incoming);
+            return false;
+        }
 
-                        // traverse and see if it has tainted return
-                        let mut res_cur = resolved.cursor();
-                        res_cur.goto_field("body");
-                        let cont = self.traverse(res_cur);
-                        self.context.pop();
+        self.summaries.insert(key.clone(), Summary::pending());
+        let before = self.context.checks();
+        self.context.enter(Context::new(
+            resolved.cursor().kind().to_string(),
+            resolved.name(),
+        ));
+        let mut res_cur = resolved.cursor();
+        res_cur.goto_field("body");
+        let ret = Taint::new_return(res_cur.clone());
+        self.traverse(res_cur);
+        self.context.pop();
 
-                        // clear local taints and graph leaves
-                        self.taints.clear_scope(&Scope::new(param_cur.clone()));
-                        self.graph.clear_scope(&Scope::new(param_cur.clone()));
+        // remember how values come back out, sources can be returned without tainted input
+        let flows = self
+            .graph
+            .take_leaves(&ret)
+            .iter()
+            .flat_map(|ret| self.graph.flows(ret, param.as_ref(), &resolved.cursor()))
+            .collect();
+        self.taints.remove(&ret);
 
-                        // trace from here if taint is returned
-                        if cont {
-                            for ret in self.taints.returns() {
-                                self.trace(cursor.clone(), ret);
-                            }
+        // clear local taints and graph leaves
+        if let Some(param) = param {
+            self.taints.clear_scope(&Scope::new(param.clone()));
+            self.graph.clear_scope(&Scope::new(param));
+        }
+
+        let checks = self
+            .context
+            .checks()
+            .into_iter()
+            .filter(|check| !before.contains(check))
+            .collect();
+        let summary = Summary::new(flows, checks);
+        self.summaries.insert(key, summary.clone());
+        self.apply(cursor, &summary, incoming);
+        false
+    }
+
+    /// trace the returned value from the call site, a tainted param comes back out
+    /// as whatever this site passed in, not what every caller did
+    fn apply(
+        &mut self,
+        cursor: Cursor<'a>,
+        summary: &Summary<'a>,
+        incoming: Option<(Taint, Vec<Cursor<'a>>)>,
+    ) {
+        for check in summary.checks() {
+            self.context.check(check.clone());
+        }
+
+        for flow in summary.flows() {
+            match &flow.origin {
+                Origin::Param => {
+                    if let Some((source, path)) = &incoming {
+                        let mut path = path.clone();
+                        path.extend(flow.path.iter().cloned());
+                        self.trace_from(cursor.clone(), source.clone(), path);
+                    }
+                }
+                Origin::Source(source) => {
+                    self.trace_from(cursor.clone(), source.clone(), flow.path.clone());
+                }
+                Origin::Vertex(vertex) => {
+                    let ret = Taint::new_call_return(cursor.clone());
+                    self.taints.push(ret.clone());
+                    self.graph.set_leaves(ret.clone(), vec![vertex.clone()]);
+                    self.trace_from(cursor.clone(), ret.clone(), flow.path.clone());
+                    self.taints.remove(&ret);
+                    self.graph.take_leaves(&ret);
+                }
+            }
+        }
+    }
+
+    /// find the function a call or callable refers to
+    fn resolve(&self, cursor: Cursor<'a>) -> Option<Resolved<'a>> {
+        self.lookup(cursor).map(|(resolved, _)| resolved)
+    }
+
+    /// find the function a call or callable refers to, and the guess it took to get there
+    fn lookup(&self, cursor: Cursor<'a>) -> Option<(Resolved<'a>, Option<Approximation>)> {
+        let method = |name: &str| {
+            self.methods
+                .get(name)
+                .map(|resolved| (resolved.clone(), Some(Approximation::UnresolvedMethod)))
+        };
+        match cursor.kind() {
+            "anonymous_function_creation_expression" | "arrow_function" => {
+                return Some((Resolved::new_closure(cursor), None));
+            }
+            "parenthesized_expression" => {
+                let mut inner = cursor.clone();
+                if inner.goto_child(1) {
+                    return self.lookup(inner);
+                }
+            }
+            "variable_name" => {
+                let name = cursor.name()?;
+                let scope = Scope::new(cursor);
+                // top level code shares its variables across files, like taints
+                let resolved = self
+                    .closures
+                    .get(&(scope.clone(), name.clone()))
+                    .or_else(|| {
+                        self.closures
+                            .iter()
+                            .find(|((at, var), _)| *var == name && scope.contains(at))
+                            .map(|(_, resolved)| resolved)
+                    })?;
+                return Some((resolved.clone(), Some(Approximation::DynamicCallable)));
+            }
+            "function_call_expression" => {
+                let mut function = cursor.clone();
+                if function.goto_field("function") {
+                    match function.kind() {
+                        "name" | "qualified_name" => {
+                            // unqualified functions fall back to the global one
+                            let name = self.qualify(&function, function.to_str(), false);
+                            return self
+                                .resolved
+                                .get(&name)
+                                .or_else(|| self.resolved.get(&Self::call_name(&function)))
+                                .map(|resolved| (resolved.clone(), None));
+                        }
+                        // calling a closure directly or through a variable
+                        _ => return self.lookup(function),
+                    }
+                }
+            }
+            "scoped_call_expression" => {
+                let (mut scope, mut name) = (cursor.clone(), cursor.clone());
+                if scope.goto_field("scope") && name.goto_field("name") {
+                    let class = match scope.to_str() {
+                        "self" | "static" => self.class_name(cursor.clone()),
+                        "parent" => None,
+                        class => Some(self.qualify(&scope, class, true)),
+                    };
+                    if let Some(resolved) =
+                        class.and_then(|c| self.resolved.get(&format!("{}::{}", c, name.to_str())))
+                    {
+                        return Some((resolved.clone(), None));
+                    }
+                    return method(name.to_str());
+                }
+            }
+            "member_call_expression" | "nullsafe_member_call_expression" => {
+                let (mut object, mut name) = (cursor.clone(), cursor.clone());
+                if object.goto_field("object") && name.goto_field("name") {
+                    if object.to_str() == "$this" {
+                        if let Some(resolved) = self
+                            .class_name(cursor.clone())
+                            .and_then(|c| self.resolved.get(&format!("{}::{}", c, name.to_str())))
+                        {
+                            return Some((resolved.clone(), None));
                         }
+                    }
+                    return method(name.to_str());
+                }
+            }
+            _ => (),
+        }
+
+        let name = Self::call_name(&cursor);
+        match self.resolved.get(&name) {
+            Some(resolved) => Some((resolved.clone(), None)),
+            None => method(&name),
+        }
+    }
+
+    /// fully qualified name of a class or function name at cursor
+    fn qualify(&self, cursor: &Cursor<'a>, name: &str, class: bool) -> String {
+        match (self.namespaces.get(&cursor.filename()), class) {
+            (Some(ns), true) => ns.qualify_class(cursor, name),
+            (Some(ns), false) => ns.qualify_function(cursor, name),
+            (None, _) => name.trim_start_matches('\\').to_string(),
+        }
+    }
 
-                        // clear return taints and graph leaves
-                        self.taints.clear_returns();
-                        self.graph.clear_returns();
+    /// fully qualified name of the class enclosing cursor
+    fn class_name(&self, cursor: Cursor<'a>) -> Option<String> {
+        let mut cur = cursor;
+        while cur.goto_parent() {
+            match cur.kind() {
+                "class_declaration" | "trait_declaration" | "interface_declaration" => {
+                    let mut name = cur.clone();
+                    if !name.goto_field("name") {
+                        return None;
                     }
+                    return Some(self.declared(&cur, name.to_str()));
                 }
-            } else {
-                // simple jump dont pass a taint or clear after
-                // (all calls to that block should taint same stuff with no input)
+                _ => (),
+            }
+        }
+        None
+    }
 
-                // if graph tells us to continue
-                if self.context.push(Context::new(
-                    resolved.cursor().kind().to_string(),
-                    resolved.name(),
-                )) {
-                    let mut res_cur = resolved.cursor();
-                    res_cur.goto_field("body");
-                    self.traverse(res_cur);
-                    self.context.pop();
+    /// fully qualified name of something declared at cursor
+    fn declared(&self, cursor: &Cursor<'a>, name: &str) -> String {
+        let ns = match self.namespaces.get(&cursor.filename()) {
+            Some(namespaces) => namespaces.namespace(cursor),
+            None => String::new(),
+        };
+        match ns.is_empty() {
+            true => name.to_string(),
+            false => format!("{}\\{}", ns, name),
+        }
+    }
+
+    /// figure out the name of the function
+    fn call_name(cursor: &Cursor<'a>) -> String {
+        match cursor.name() {
+            Some(name) => name,
+            None => cursor.to_string().replace("\"", "").replace("'", ""),
+        }
+    }
+
+    /// follow the callable argument of a call according to its callback rule,
+    /// returns true if taint passes through the call
+    fn callback(
+        &mut self,
+        cursor: Cursor<'a>,
+        rule: Callback,
+        index: Option<usize>,
+        source: Option<Taint>,
+        path: Option<Vec<Cursor<'a>>>,
+    ) -> bool {
+        let callable = Self::argument(cursor.clone(), rule.callable());
+        let resolved = match callable
+            .clone()
+            .and_then(|callable| self.callable(callable))
+        {
+            Some(resolved) => resolved,
+            None => return true,
+        };
+        // array_map($fn, ...) calls whatever closure the variable held
+        if callable.is_some_and(|callable| callable.kind() == "variable_name") {
+            self.graph
+                .approximate(cursor.clone(), Approximation::DynamicCallable);
+        }
+
+        if let (Some(index), Some(source), Some(path)) = (index, source, path) {
+            let params = rule.params(index, resolved.parameters().len());
+            if params.is_empty() {
+                return true;
+            }
+            for param in params {
+                self.invoke(
+                    cursor.clone(),
+                    resolved.clone(),
+                    Some(param),
+                    Some(source.clone()),
+                    Some(path.clone()),
+                );
+            }
+            // returned data comes from the callback now
+            !rule.returns()
+        } else {
+            self.invoke(cursor, resolved, None, None, None);
+            true
+        }
+    }
+
+    /// resolve a callable value, 'name', 'Class::method', [$obj, 'method'] or a closure
+    fn callable(&self, cursor: Cursor<'a>) -> Option<Resolved<'a>> {
+        match cursor.kind() {
+            // names in strings are always fully qualified
+            "string" => {
+                let name = cursor.string_value()?;
+                let name = name.trim_start_matches('\\');
+                let method = name.rsplit("::").next().unwrap_or_default();
+                self.resolved
+                    .get(name)
+                    .or_else(|| self.methods.get(method))
+                    .cloned()
+            }
+            "array_creation_expression" => {
+                // the last element names the method
+                let mut last = None;
+                let mut element = cursor;
+                if element.goto_first_child() {
+                    loop {
+                        if element.kind() == "array_element_initializer" {
+                            last = Some(element.clone());
+                        }
+                        if !element.goto_next_sibling() {
+                            break;
+                        }
+                    }
                 }
+                let mut method = last?;
+                method.goto_first_child();
+                self.callable(method)
             }
+            _ => self.closure_value(cursor),
         }
+    }
 
-        passes_taint
+    /// a string callable passed to a callback function that we cant resolve
+    fn named_callable(&self, cursor: Cursor<'a>, index: usize) -> Option<Cursor<'a>> {
+        let rule = self.callbacks.get(&Self::call_name(&cursor))?;
+        if rule.params(index, 1).is_empty() {
+            return None;
+        }
+        let callable = Self::argument(cursor, rule.callable())?;
+        match callable.kind() == "string" && self.callable(callable.clone()).is_none() {
+            true => Some(callable),
+            false => None,
+        }
+    }
+
+    /// name stored data goes by, "stored option:my_key", "*" when the key isnt known
+    fn store_label(&self, cursor: Cursor<'a>, store: &Store) -> String {
+        let key = store
+            .key()
+            .and_then(|n| Self::argument(cursor, n))
+            .and_then(|key| self.constants.eval(key))
+            .unwrap_or_else(|| "*".to_string());
+        format!("stored {}:{}", store.store(), key)
+    }
+
+    /// variable coerced by settype to a numeric or boolean type
+    fn settype_target(cursor: Cursor<'a>) -> Option<Cursor<'a>> {
+        let var = Self::argument(cursor.clone(), 0)?;
+        let kind = Self::argument(cursor, 1)?.string_value()?.to_lowercase();
+        match var.kind() == "variable_name"
+            && ["int", "integer", "float", "double", "bool", "boolean"].contains(&kind.as_str())
+        {
+            true => Some(var),
+            false => None,
+        }
+    }
+
+    /// declared type of a param, if it is a scalar a string cant get through
+    fn param_type(param: Cursor<'a>) -> Option<Cursor<'a>> {
+        let mut type_node = param;
+        if !type_node.goto_parent() || !type_node.goto_field("type") {
+            return None;
+        }
+        let name = type_node
+            .to_str()
+            .trim_start_matches('?')
+            .trim()
+            .to_lowercase();
+        match ["int", "float", "bool"].contains(&name.as_str()) {
+            true => Some(type_node),
+            false => None,
+        }
+    }
+
+    /// expression passed as the nth argument of a call
+    fn argument(cursor: Cursor<'a>, n: usize) -> Option<Cursor<'a>> {
+        let mut args = cursor;
+        if !args.goto_field("arguments") || !args.goto_first_child() {
+            return None;
+        }
+        let mut i = 0;
+        loop {
+            if args.kind() == "argument" {
+                if i == n {
+                    args.goto_first_child();
+                    while !args.raw_cursor().node().is_named() && args.goto_next_sibling() {}
+                    return Some(args);
+                }
+                i += 1;
+            }
+            if !args.goto_next_sibling() {
+                return None;
+            }
+        }
+    }
+
+    /// closures passed as arguments to a call
+    fn closure_arguments(&self, cursor: Cursor<'a>) -> Vec<Resolved<'a>> {
+        let mut v = Vec::new();
+        let mut args = cursor;
+        if !args.goto_field("arguments") || !args.goto_first_child() {
+            return v;
+        }
+        loop {
+            if args.kind() == "argument" {
+                let mut value = args.clone();
+                value.goto_first_child();
+                if let Some(Resolved::Closure { cursor }) = self.closure_value(value) {
+                    v.push(Resolved::new_closure(cursor));
+                }
+            }
+            if !args.goto_next_sibling() {
+                break;
+            }
+        }
+        v
+    }
+
+    /// resolve an expression if it is a closure or a variable holding one
+    fn closure_value(&self, cursor: Cursor<'a>) -> Option<Resolved<'a>> {
+        match cursor.kind() {
+            "anonymous_function_creation_expression"
+            | "arrow_function"
+            | "parenthesized_expression"
+            | "variable_name" => self.resolve(cursor),
+            _ => None,
+        }
+    }
+
+    /// closures capture by value when defined, copy taints into the closure scope
+    fn capture(&mut self, cursor: Cursor<'a>) {
+        let outer = Scope::new(cursor.clone());
+        let params: Vec<Option<String>> = Resolved::new_closure(cursor.clone())
+            .parameters()
+            .iter()
+            .map(|p| p.name())
+            .collect();
+
+        let mut captured = Vec::new();
+        for motion in Traversal::new(&cursor) {
+            if let Order::Enter(cur) = motion {
+                if cur.kind() != "variable_name" || params.contains(&cur.name()) {
+                    continue;
+                }
+                // arrow functions capture everything they use, closures only `use` vars
+                let mut parent = cur.clone();
+                parent.goto_parent();
+                if cursor.kind() == "arrow_function"
+                    || parent.kind() == "anonymous_function_use_clause"
+                    || parent.kind() == "by_ref"
+                {
+                    captured.push(cur);
+                }
+            }
+        }
+
+        for var in captured {
+            let lookup = Taint {
+                kind: TaintKind::Variable,
+                name: var.name().unwrap_or_default(),
+                scope: outer.clone(),
+            };
+            if let Some(taint) = self.taints.get(&lookup) {
+                if outer.contains(&taint.scope) {
+                    let path = vec![var.clone()];
+                    self.push_taint(var.clone(), taint, Taint::new_variable(var), path);
+                }
+            }
+        }
+    }
+
+    /// traverse a statically resolvable include as if it were inline
+    fn include(&mut self, cursor: Cursor<'a>) {
+        let mut path = cursor.clone();
+        path.goto_first_child();
+        while !path.raw_cursor().node().is_named() {
+            if !path.goto_next_sibling() {
+                return;
+            }
+        }
+
+        let resolved = match self
+            .constants
+            .eval(path)
+            .and_then(|p| self.find_file(&cursor, &p))
+        {
+            Some(resolved) => resolved,
+            None => return,
+        };
+
+        // the included code runs in the scope of the include site
+        let scope = self.scope(cursor.clone());
+        let outer = match scope.is_toplevel() {
+            true => self.include_scope.take(),
+            false => self.include_scope.replace(scope),
+        };
+
+        // guard against files including each other
+        if self.context.push(Context::new(
+            "include".to_string(),
+            resolved.cursor().filename(),
+        )) {
+            self.traverse(resolved.cursor());
+            self.context.pop();
+        }
+        self.include_scope = outer;
+    }
+
+    /// scope variables at a cursor live in, the include site for top level code of included files
+    fn scope(&self, cursor: Cursor<'a>) -> Scope {
+        let scope = Scope::new(cursor);
+        match (scope.is_toplevel(), &self.include_scope) {
+            (true, Some(include)) => include.clone(),
+            _ => scope,
+        }
+    }
+
+    /// find the program for an include path, relative to the including file
+    fn find_file(&self, cursor: &Cursor<'a>, path: &str) -> Option<Resolved<'a>> {
+        let path = match path.starts_with('/') {
+            true => normalize(path),
+            false => normalize(&format!("{}/{}", dirname(&cursor.filename()), path)),
+        };
+        if let Some(resolved) = self.resolved.get(&path) {
+            return Some(resolved.clone());
+        }
+
+        // file names may be relative to somewhere else, match the tail
+        for file in self.files.iter() {
+            let name = normalize(&file.name());
+            let (long, short) = match name.len() > path.len() {
+                true => (&name, &path),
+                false => (&path, &name),
+            };
+            let short = short.trim_start_matches("../");
+            if long.ends_with(&format!("/{}", short)) || long == short {
+                return self.resolved.get(&name).cloned();
+            }
+        }
+
+        None
     }
 
     /// get a taint associated with this cursor
@@ -257,16 +1048,22 @@ impl<'a> Analyzer<'a> {
                             return None;
                         }
                     }
-                    if s == "object" {
-                        return None;
-                    }
                 }
-                // check for taint
-                if let Some(taint) = self.taints.get(&Taint::new_variable(cursor.clone())) {
-                    // check if in scope
-                    let cur_scope = Scope::new(cursor.clone());
-                    if cur_scope.contains(&taint.scope) {
-                        return Some(taint);
+                // destructuring and loop targets are written, not read
+                if Self::is_target(cursor.clone()) {
+                    return None;
+                }
+                // check for taint, in the file itself or where it was included
+                let mut scopes = vec![Scope::new(cursor.clone())];
+                scopes.push(self.scope(cursor.clone()));
+                scopes.dedup();
+                for scope in scopes {
+                    let mut variable = Taint::new_variable(cursor.clone());
+                    variable.scope = scope;
+                    if let Some(taint) = self.taints.get(&variable) {
+                        if variable.scope.contains(&taint.scope) {
+                            return Some(taint);
+                        }
                     }
                 }
 
@@ -276,20 +1073,264 @@ impl<'a> Analyzer<'a> {
         }
     }
 
-    /// call functions that are hooked
-    fn handle_hook(&mut self, cursor: Cursor<'a>) {
-        let mut cursor = cursor;
-        cursor.goto_field("arguments");
-        let mut traversal = Traversal::new(&cursor);
-        while let Some(motion) = traversal.next() {
+    /// variables written by a list() or [] destructuring
+    fn targets(cursor: Cursor<'a>) -> Vec<Cursor<'a>> {
+        let mut v = Vec::new();
+        for motion in Traversal::new(&cursor) {
+            if let Order::Enter(cur) = motion {
+                if cur.kind() == "variable_name" {
+                    v.push(cur);
+                }
+            }
+        }
+        v
+    }
+
+    /// key and value variables of a foreach, skipping the iterated expression and body
+    fn loop_targets(cursor: Cursor<'a>) -> Vec<Cursor<'a>> {
+        let mut v = Vec::new();
+        let mut cur = cursor;
+        if !cur.goto_first_child() {
+            return v;
+        }
+        loop {
+            if cur.raw_cursor().node().is_named()
+                && cur.get_index() > 0
+                && cur.field() != Some("body")
+            {
+                v.extend(Self::targets(cur.clone()));
+            }
+            if !cur.goto_next_sibling() {
+                break;
+            }
+        }
+        v
+    }
+
+    /// true if this variable is a destructuring or foreach target
+    fn is_target(cursor: Cursor<'a>) -> bool {
+        let mut cur = cursor;
+        loop {
+            let body = cur.field() == Some("body");
+            let index = cur.get_index();
+            if !cur.goto_parent() {
+                return false;
+            }
+            match cur.kind() {
+                "list_literal" => {
+                    // the outermost list decides
+                    let mut parent = cur.clone();
+                    parent.goto_parent();
+                    if parent.kind() != "list_literal" && parent.kind() != "pair" {
+                        return cur.field() == Some("left") || parent.kind() == "foreach_statement";
+                    }
+                }
+                "pair" | "by_ref" | "array_element_initializer" => (),
+                "foreach_statement" => return index > 0 && !body,
+                _ => return false,
+            }
+        }
+    }
+
+    /// if an assignment writes to a by reference foreach variable, get the iterated array
+    fn reference_array(cursor: Cursor<'a>) -> Option<Cursor<'a>> {
+        let mut left = cursor.clone();
+        if !left.goto_field("left") || left.kind() != "variable_name" {
+            return None;
+        }
+        let name = left.name();
+        for cur in Trace::new(cursor.clone()) {
+            if cur.kind() != "foreach_statement" {
+                continue;
+            }
+            for var in Self::loop_targets(cur.clone()) {
+                let mut parent = var.clone();
+                parent.goto_parent();
+                if parent.kind() == "by_ref" && var.name() == name {
+                    let mut array = cur.clone();
+                    array.goto_first_child();
+                    while !array.raw_cursor().node().is_named() {
+                        array.goto_next_sibling();
+                    }
+                    if array.kind() == "variable_name" {
+                        return Some(array);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// call functions that are hooked, tainting the params wordpress fills with user data
+    fn handle_hook(&mut self, cursor: Cursor<'a>, hook: Hook) {
+        let resolved = match Self::hook_callable(cursor.clone(), &hook)
+            .and_then(|callable| self.callable(callable))
+        {
+            Some(resolved) => resolved,
+            None => return,
+        };
+
+        let params = hook.params(&Self::hook_tag(cursor.clone()).unwrap_or_default());
+        if params.is_empty() {
+            self.invoke(cursor, resolved, None, None, None);
+            return;
+        }
+
+        // params are sources named after the hook function, add_shortcode etc
+        let source = Taint::new_source(Self::call_name(&cursor));
+        for param in params {
+            self.invoke(
+                cursor.clone(),
+                resolved.clone(),
+                Some(param),
+                Some(source.clone()),
+                Some(vec![cursor.clone()]),
+            );
+        }
+    }
+
+    /// externally reachable code, top level file code and hook registrations
+    fn entry_points(&self) -> Vec<(EntryPoint, Cursor<'a>)> {
+        let mut v = Vec::new();
+        // crawl each file ( since with wordpress these can sometimes still be accessed )
+        for file in self.files.iter() {
+            let cursor = Cursor::from_file(file);
+            let privilege = match Self::direct_access(cursor.clone()) {
+                true => "nopriv",
+                false => "included",
+            };
+            let entry = EntryPoint::new("file".to_string(), file.name(), privilege.to_string());
+            v.push((entry, cursor));
+        }
+
+        for cur in self.registrations.iter() {
+            if let Some(hook) = self.hooks.get(&Self::call_name(cur)) {
+                if let Some(entry) = self.hook_entry(cur.clone(), hook) {
+                    v.push((entry, cur.clone()));
+                }
+            }
+        }
+
+        v
+    }
+
+    /// entry point created by a hook registration, if it is one
+    fn hook_entry(&self, cursor: Cursor<'a>, hook: &Hook) -> Option<EntryPoint> {
+        let mut tag = Self::hook_tag(cursor.clone()).unwrap_or_default();
+
+        // hooks run by cron
+        let (kind, mut privilege) = match self.scheduled.get(&tag) {
+            Some(entry) => entry.clone(),
+            None => hook.entry(&tag)?,
+        };
+
+        if hook.key().is_some() {
+            // routes are named by namespace and path
+            if let Some(route) = Self::argument(cursor.clone(), 1).and_then(|r| r.string_value()) {
+                tag.push_str(&route);
+            }
+            // without a real permission callback anyone can call it
+            if let Some(permission) = hook.permission() {
+                let args = Self::argument(cursor.clone(), hook.callable()?)?;
+                if Self::array_value(args, permission)
+                    .is_none_or(|p| p.string_value().as_deref() == Some("__return_true"))
+                {
+                    privilege = "nopriv".to_string();
+                }
+            }
+        }
+
+        Some(EntryPoint::new(kind, tag, privilege))
+    }
+
+    /// files that exit unless loaded by wordpress cant be requested directly
+    fn direct_access(cursor: Cursor<'a>) -> bool {
+        for motion in cursor.traverse_block() {
             if let Order::Enter(cur) = motion {
-                if cur.kind() == "argument" {
-                    if cur.to_string().len() > 2 {
-                        self.call(cur.clone(), None, None, None);
+                if cur.kind() == "function_call_expression" && Self::call_name(&cur) == "defined" {
+                    let constant = Self::argument(cur, 0).and_then(|c| c.string_value());
+                    if let Some("ABSPATH" | "WPINC") = constant.as_deref() {
+                        return false;
                     }
                 }
             }
         }
+        true
+    }
+
+    /// run the callables registered for a hook, returns true if taint passes through
+    fn dispatch(
+        &mut self,
+        cursor: Cursor<'a>,
+        first: usize,
+        index: Option<usize>,
+        source: Option<Taint>,
+        path: Option<Vec<Cursor<'a>>>,
+    ) -> bool {
+        let (index, source, path) = match (index, source, path) {
+            (Some(index), Some(source), Some(path)) if index >= first => (index, source, path),
+            _ => return true,
+        };
+        let tag = match Self::hook_tag(cursor.clone()) {
+            Some(tag) => tag,
+            None => return true,
+        };
+
+        let mut passes_taint = true;
+        for callable in self.registered.get(&tag).cloned().unwrap_or_default() {
+            if let Some(resolved) = self.callable(callable) {
+                // filters return the value now
+                passes_taint = false;
+                self.invoke(
+                    cursor.clone(),
+                    resolved,
+                    Some(index - first),
+                    Some(source.clone()),
+                    Some(path.clone()),
+                );
+            }
+        }
+
+        passes_taint
+    }
+
+    /// static hook name of a hook call
+    fn hook_tag(cursor: Cursor<'a>) -> Option<String> {
+        Self::argument(cursor, 0)?.string_value()
+    }
+
+    /// the callable argument of a hook registration
+    fn hook_callable(cursor: Cursor<'a>, hook: &Hook) -> Option<Cursor<'a>> {
+        let callable = Self::argument(cursor, hook.callable()?)?;
+        match hook.key() {
+            Some(key) => Self::array_value(callable, key),
+            None => Some(callable),
+        }
+    }
+
+    /// value stored under a string key in an array literal
+    fn array_value(cursor: Cursor<'a>, key: &str) -> Option<Cursor<'a>> {
+        let mut element = cursor;
+        if element.kind() != "array_creation_expression" || !element.goto_first_child() {
+            return None;
+        }
+        loop {
+            if element.kind() == "array_element_initializer" {
+                let mut value = element.clone();
+                value.goto_first_child();
+                if value.string_value().as_deref() == Some(key) {
+                    // skip the key and arrow
+                    while value.goto_next_sibling() {
+                        if value.raw_cursor().node().is_named() {
+                            return Some(value);
+                        }
+                    }
+                }
+            }
+            if !element.goto_next_sibling() {
+                return None;
+            }
+        }
     }
 
     /// create taint and graph it
@@ -302,18 +1343,93 @@ impl<'a> Analyzer<'a> {
 
     /// load resolved items to analyzer
     fn resolve_files(&mut self) {
+        for file in self.files.iter() {
+            self.namespaces.insert(file.name(), Namespaces::new(file));
+        }
+
         for file in self.files.iter() {
             for motion in file.traverse() {
                 if let Order::Enter(cur) = motion.clone() {
                     match cur.kind() {
-                        "function_definition" | "method_declaration" => {
+                        "function_definition" => {
+                            if let Some(n) = cur.name() {
+                                let name = self.declared(&cur, &n);
+                                self.resolved.insert(name, Resolved::new_function(cur));
+                            }
+                        }
+                        "method_declaration" => {
                             if let Some(n) = cur.name() {
-                                self.resolved.insert(n, Resolved::new_function(cur));
+                                if let Some(class) = self.class_name(cur.clone()) {
+                                    self.resolved.insert(
+                                        format!("{}::{}", class, n),
+                                        Resolved::new_function(cur.clone()),
+                                    );
+                                }
+                                self.methods.insert(n, Resolved::new_function(cur));
                             }
                         }
+                        // closures assigned to a variable can be called through it
+                        "anonymous_function_creation_expression" | "arrow_function" => {
+                            let mut parent = cur.clone();
+                            if cur.field() == Some("right") && parent.goto_parent() {
+                                let mut left = parent.clone();
+                                if left.goto_field("left") && left.kind() == "variable_name" {
+                                    if let Some(n) = left.name() {
+                                        let key = (Scope::new(left.clone()), n);
+                                        self.closures.insert(key, Resolved::new_closure(cur));
+                                    }
+                                }
+                            }
+                        }
+                        // checks that guard variables in branches
+                        "if_statement" => {
+                            self.guards.extend(Guard::from_if(cur, &self.checks));
+                        }
+                        // programs are keyed by file name for includes
                         "program" => {
                             self.resolved
-                                .insert("ROOT".to_string(), Resolved::new_root(cur));
+                                .insert(normalize(&cur.filename()), Resolved::new_root(cur));
+                        }
+                        // constants for evaluating include paths
+                        "const_element" => {
+                            let mut name = cur.clone();
+                            name.goto_first_child();
+                            let mut value = name.clone();
+                            while value.goto_next_sibling() {
+                                if value.raw_cursor().node().is_named() {
+                                    self.constants.define(name.to_string(), value);
+                                    break;
+                                }
+                            }
+                        }
+                        // remember which callables are registered for each hook name
+                        "function_call_expression" => {
+                            if Self::call_name(&cur) == "define" {
+                                let name =
+                                    Self::argument(cur.clone(), 0).and_then(|n| n.string_value());
+                                if let (Some(name), Some(value)) =
+                                    (name, Self::argument(cur.clone(), 1))
+                                {
+                                    self.constants.define(name, value);
+                                }
+                            }
+                            if let Some(hook) = self.hooks.get(&Self::call_name(&cur)) {
+                                if let (Some(tag), Some(callable)) = (
+                                    Self::hook_tag(cur.clone()),
+                                    Self::hook_callable(cur.clone(), hook),
+                                ) {
+                                    self.registered.entry(tag).or_default().push(callable);
+                                    self.registrations.push(cur.clone());
+                                }
+                                // hook names scheduled with cron become entry points
+                                if let Some(index) = hook.schedule() {
+                                    let tag = Self::argument(cur.clone(), index)
+                                        .and_then(|t| t.string_value());
+                                    if let (Some(tag), Some(entry)) = (tag, hook.entry("")) {
+                                        self.scheduled.insert(tag, entry);
+                                    }
+                                }
+                            }
                         }
                         _ => (),
                     }
@@ -322,3 +1438,101 @@ impl<'a> Analyzer<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::finding::Confidence;
+
+    #[test]
+    fn findings_carry_their_entry_point() {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let rules = Rules::from_yaml(&format!("{}/new.yaml", root)).unwrap();
+        let file = File::new(&format!("{}/fixtures/hooks.php", root)).unwrap();
+        let mut analyzer = Analyzer::new(vec![&file], &rules);
+
+        let mut entries: Vec<(String, String, String)> = analyzer
+            .graph()
+            .match_rules(&rules)
+            .iter()
+            .filter_map(|finding| finding.entry())
+            .filter(|entry| entry.kind != "file")
+            .map(|entry| {
+                let (kind, name) = (entry.kind.clone(), entry.name.clone());
+                (kind, name, entry.privilege.clone())
+            })
+            .collect();
+        entries.sort();
+        // the rest param is a source on its own and read through get_param
+        entries.dedup();
+
+        let expected = [
+            ("ajax", "wp_ajax_go", "authenticated"),
+            ("ajax", "wp_ajax_nopriv_pub", "nopriv"),
+            ("cron", "my_cron", "none"),
+            ("rest", "ns/v1/x", "nopriv"),
+            ("shortcode", "box", "contributor"),
+        ];
+        let expected: Vec<(String, String, String)> = expected
+            .iter()
+            .map(|(k, n, p)| (k.to_string(), n.to_string(), p.to_string()))
+            .collect();
+        assert_eq!(entries, expected);
+    }
+
+    #[test]
+    fn shared_functions_run_for_each_entry_point() {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let rules = Rules::from_yaml(&format!("{}/new.yaml", root)).unwrap();
+        let file = File::new(&format!("{}/fixtures/entries.php", root)).unwrap();
+        let mut analyzer = Analyzer::new(vec![&file], &rules);
+
+        let mut entries: Vec<String> = analyzer
+            .graph()
+            .match_rules(&rules)
+            .iter()
+            .filter_map(|finding| Some((finding.vuln(), finding.entry()?)))
+            .map(|(vuln, entry)| format!("{} {} {}", vuln, entry.name, entry.privilege))
+            .collect();
+        entries.sort();
+        // the settings run checked the nonce and capability before storing
+        assert_eq!(
+            entries,
+            [
+                "csrf wp_ajax_nopriv_guest nopriv",
+                "missing-capability wp_ajax_nopriv_guest nopriv",
+                "xss admin_post_settings authenticated",
+                "xss wp_ajax_nopriv_guest nopriv"
+            ]
+        );
+    }
+
+    #[test]
+    fn findings_lose_confidence_for_each_guess() {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let rules = Rules::from_yaml(&format!("{}/new.yaml", root)).unwrap();
+        let file = File::new(&format!("{}/fixtures/confidence.php", root)).unwrap();
+        let mut analyzer = Analyzer::new(vec![&file], &rules);
+
+        let mut found: Vec<(usize, Vec<Approximation>, Confidence)> = analyzer
+            .graph()
+            .match_rules(&rules)
+            .iter()
+            .map(|finding| {
+                let line = finding.path()[0].raw_cursor().node().start_position().row + 1;
+                let approximations = finding.approximations().iter().copied().collect();
+                (line, approximations, finding.confidence())
+            })
+            .collect();
+        found.sort();
+        assert_eq!(
+            found,
+            [
+                (2, vec![], Confidence::High),
+                (4, vec![Approximation::UnresolvedMethod], Confidence::Medium),
+                (9, vec![Approximation::DynamicCallable], Confidence::Medium),
+                (14, vec![], Confidence::High),
+            ]
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use crate::tree::cursor::*;
+use crate::tree::traverser::*;
+use std::collections::HashMap;
+
+/// constants from define() and const, used to evaluate strings statically
+pub struct Constants<'a> {
+    defined: HashMap<String, Cursor<'a>>,
+}
+
+impl<'a> Default for Constants<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Constants<'a> {
+    pub fn new() -> Self {
+        Self {
+            defined: HashMap::new(),
+        }
+    }
+
+    pub fn define(&mut self, name: String, value: Cursor<'a>) {
+        self.defined.insert(name, value);
+    }
+
+    /// evaluate a string expression if it is statically known
+    pub fn eval(&self, cursor: Cursor<'a>) -> Option<String> {
+        self.eval_depth(cursor, 0)
+    }
+
+    fn eval_depth(&self, cursor: Cursor<'a>, depth: usize) -> Option<String> {
+        // constants defined in terms of themselves
+        if depth > 16 {
+            return None;
+        }
+
+        match cursor.kind() {
+            "string" => cursor.string_value(),
+            "encapsed_string" => {
+                // only without interpolation
+                for motion in cursor.traverse() {
+                    if let Order::Enter(cur) = motion {
+                        if cur.kind() == "variable_name" {
+                            return None;
+                        }
+                    }
+                }
+                Some(cursor.to_str().trim_matches('"').to_string())
+            }
+            "binary_expression" => {
+                let mut op = cursor.clone();
+                if !op.goto_field("operator") || op.to_str() != "." {
+                    return None;
+                }
+                let (mut left, mut right) = (cursor.clone(), cursor.clone());
+                left.goto_field("left");
+                right.goto_field("right");
+                let left = self.eval_depth(left, depth + 1)?;
+                let right = self.eval_depth(right, depth + 1)?;
+                Some(format!("{}{}", left, right))
+            }
+            "parenthesized_expression" | "argument" => {
+                let mut inner = cursor.clone();
+                inner.goto_first_child();
+                while !inner.raw_cursor().node().is_named() {
+                    if !inner.goto_next_sibling() {
+                        return None;
+                    }
+                }
+                self.eval_depth(inner, depth + 1)
+            }
+            "name" => match cursor.to_str() {
+                "__FILE__" => Some(cursor.filename()),
+                "__DIR__" => Some(dirname(&cursor.filename())),
+                name => self
+                    .defined
+                    .get(name)
+                    .and_then(|value| self.eval_depth(value.clone(), depth + 1)),
+            },
+            "function_call_expression" => {
+                let name = cursor.name()?;
+                let mut args = cursor.clone();
+                args.goto_field("arguments");
+                args.goto_first_child();
+                while args.kind() != "argument" {
+                    if !args.goto_next_sibling() {
+                        return None;
+                    }
+                }
+                let arg = self.eval_depth(args, depth + 1)?;
+                match name.as_str() {
+                    "dirname" => Some(dirname(&arg)),
+                    "plugin_dir_path" => Some(format!("{}/", dirname(&arg))),
+                    "trailingslashit" => Some(format!("{}/", arg.trim_end_matches('/'))),
+                    "untrailingslashit" => Some(arg.trim_end_matches('/').to_string()),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// parent directory of a path
+pub fn dirname(path: &str) -> String {
+    match path.trim_end_matches('/').rsplit_once('/') {
+        Some(("", _)) => "/".to_string(),
+        Some((dir, _)) => dir.to_string(),
+        None => ".".to_string(),
+    }
+}
+
+/// resolve . and .. components and duplicate slashes
+pub fn normalize(path: &str) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => (),
+            ".." => {
+                if parts.last().is_none_or(|p| *p == "..") {
+                    parts.push(part);
+                } else {
+                    parts.pop();
+                }
+            }
+            _ => parts.push(part),
+        }
+    }
+
+    let joined = parts.join("/");
+    match path.starts_with('/') {
+        true => format!("/{}", joined),
+        false => joined,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_paths() {
+        assert_eq!(normalize("/a/./b//c/"), "/a/b/c");
+        assert_eq!(normalize("/a/b/../c"), "/a/c");
+        assert_eq!(normalize("a/../../b"), "../b");
+        assert_eq!(normalize("../../a"), "../../a");
+        assert_eq!(normalize("./a"), "a");
+    }
+
+    #[test]
+    fn dirname_of_paths() {
+        assert_eq!(dirname("/plugin/includes/file.php"), "/plugin/includes");
+        assert_eq!(dirname("/plugin/includes/"), "/plugin");
+        assert_eq!(dirname("/file.php"), "/");
+        assert_eq!(dirname("file.php"), ".");
+    }
+}
@@ -0,0 +1,178 @@
+use crate::analyzer::analyzer::*;
+use crate::graph::rules::*;
+use crate::tree::file::*;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// what a fixture line says should be found on it,
+/// `// expect: xss, sqli` or `// expect-none`, templates can use `<!-- expect: xss -->`
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Expect {
+    Vulns(BTreeSet<String>),
+    Nothing,
+}
+
+/// a sink line where the findings and the annotations disagree
+pub struct Mismatch {
+    pub file: String,
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.file, self.line, self.message)
+    }
+}
+
+/// analyze fixtures and compare the sink line of every finding to the annotations,
+/// php files are analyzed alone and directories as one project.
+/// returns the number of fixtures run and every mismatch
+pub fn run(
+    rules: &Rules,
+    paths: &[String],
+) -> Result<(usize, Vec<Mismatch>), Box<dyn std::error::Error>> {
+    let mut count = 0;
+    let mut mismatches = Vec::new();
+
+    for path in paths {
+        let mut projects: Vec<Vec<String>> = Vec::new();
+        match Path::new(path).is_dir() {
+            true => {
+                let mut entries: Vec<_> = std::fs::read_dir(path)?
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path())
+                    .collect();
+                entries.sort();
+                for entry in entries {
+                    let name = entry.to_string_lossy().to_string();
+                    if entry.is_dir() {
+                        projects.push(php_files(&entry));
+                    } else if name.ends_with(".php") {
+                        projects.push(vec![name]);
+                    }
+                }
+            }
+            false => projects.push(vec![path.clone()]),
+        }
+
+        for project in projects {
+            count += 1;
+            let files = project
+                .iter()
+                .map(|name| File::new(name))
+                .collect::<Result<Vec<File>, _>>()?;
+            mismatches.extend(compare(rules, &files));
+        }
+    }
+
+    Ok((count, mismatches))
+}
+
+/// every php file under a directory
+fn php_files(dir: &Path) -> Vec<String> {
+    let mut names = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            if path.is_dir() {
+                names.extend(php_files(&path));
+            } else if path.extension().is_some_and(|ext| ext == "php") {
+                names.push(path.to_string_lossy().to_string());
+            }
+        }
+    }
+    names.sort();
+    names
+}
+
+fn compare(rules: &Rules, files: &[File]) -> Vec<Mismatch> {
+    let mut analyzer = Analyzer::new(files.iter().collect(), rules);
+
+    // vulns found at each sink line of each file
+    let mut found: BTreeMap<(String, usize), BTreeSet<String>> = BTreeMap::new();
+    for finding in analyzer.graph().match_rules(rules).iter() {
+        if let Some(sink) = finding.path().first() {
+            found
+                .entry((sink.filename(), sink.row()))
+                .or_default()
+                .insert(finding.vuln().to_string());
+        }
+    }
+
+    let mut mismatches = Vec::new();
+    let mut mismatch = |file: &str, line: usize, message: String| {
+        mismatches.push(Mismatch {
+            file: file.to_string(),
+            line,
+            message,
+        })
+    };
+
+    for file in files {
+        let expected = annotations(file.get_source());
+        for (line, expect) in expected.iter() {
+            let vulns = found.remove(&(file.name(), *line)).unwrap_or_default();
+            match expect {
+                Expect::Vulns(wanted) => {
+                    for vuln in wanted.difference(&vulns) {
+                        mismatch(&file.name(), *line, format!("missing {}", vuln));
+                    }
+                    for vuln in vulns.difference(wanted) {
+                        mismatch(&file.name(), *line, format!("unexpected {}", vuln));
+                    }
+                }
+                Expect::Nothing => {
+                    for vuln in vulns.iter() {
+                        mismatch(&file.name(), *line, format!("unexpected {}", vuln));
+                    }
+                }
+            }
+        }
+    }
+
+    // findings on lines nobody annotated
+    for ((file, line), vulns) in found {
+        for vuln in vulns {
+            mismatch(&file, line, format!("unexpected {}, not annotated", vuln));
+        }
+    }
+
+    mismatches
+}
+
+/// annotations by line number, counting from 1
+fn annotations(source: &str) -> BTreeMap<usize, Expect> {
+    let mut expected = BTreeMap::new();
+    for (i, line) in source.lines().enumerate() {
+        if line.contains("expect-none") {
+            expected.insert(i + 1, Expect::Nothing);
+        } else if let Some((_, vulns)) = line.split_once("expect:") {
+            let vulns = vulns
+                .split(',')
+                .map(|vuln| {
+                    let vuln = vuln.trim().trim_end_matches("*/").trim_end_matches("-->");
+                    vuln.trim().to_string()
+                })
+                .filter(|vuln| !vuln.is_empty())
+                .collect();
+            expected.insert(i + 1, Expect::Vulns(vulns));
+        }
+    }
+    expected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixtures_match_expectations() {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let rules = Rules::load(&format!("{}/new.yaml", root)).unwrap();
+        let (count, mismatches) = run(&rules, &[format!("{}/fixtures", root)]).unwrap();
+        assert!(count > 0);
+        let report: Vec<String> = mismatches.iter().map(|m| m.to_string()).collect();
+        assert!(report.is_empty(), "{}", report.join("\n"));
+    }
+}
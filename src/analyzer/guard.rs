@@ -0,0 +1,256 @@
+use crate::tree::cursor::*;
+use crate::tree::traverser::*;
+use std::collections::HashMap;
+
+// statements and calls that leave the current block
+const EXITS: [&str; 6] = [
+    "return_statement",
+    "exit_statement",
+    "throw_expression",
+    "throw_statement",
+    "break_statement",
+    "continue_statement",
+];
+const EXIT_CALLS: [&str; 6] = [
+    "exit",
+    "die",
+    "wp_die",
+    "wp_send_json",
+    "wp_send_json_error",
+    "wp_send_json_success",
+];
+
+/// a check on a variable that holds for a byte range of a file,
+/// if (is_numeric($id)) { here } or if (!is_numeric($id)) return; here
+#[derive(Clone, Debug)]
+pub struct Guard<'a> {
+    name: String,
+    check: Cursor<'a>,
+    start: usize,
+    end: usize,
+}
+
+impl<'a> Guard<'a> {
+    /// guards an if statement sets up, in its branches and after it if it exits early
+    pub fn from_if(cursor: Cursor<'a>, checks: &HashMap<String, Option<Vec<u32>>>) -> Vec<Self> {
+        let mut guards = Vec::new();
+        let mut condition = cursor.clone();
+        if !condition.goto_field("condition") {
+            return guards;
+        }
+
+        // the check passed in the body
+        let mut body = cursor.clone();
+        if body.goto_field("body") {
+            for (name, check) in Self::conditions(condition.clone(), false, checks) {
+                guards.push(Self::new(name, check, &body));
+            }
+        }
+
+        // the check failed in the else branch
+        let mut alternative = cursor.clone();
+        if alternative.goto_field("alternative")
+            && alternative.kind() == "else_clause"
+            && alternative.goto_field("body")
+        {
+            for (name, check) in Self::conditions(condition.clone(), true, checks) {
+                guards.push(Self::new(name, check, &alternative));
+            }
+        }
+
+        // the check failed and the body leaves, so it passed for the rest of the block
+        let mut block = cursor.clone();
+        if Self::exits(body) && block.goto_parent() {
+            let start = cursor.raw_cursor().node().end_byte();
+            let end = block.raw_cursor().node().end_byte();
+            for (name, check) in Self::conditions(condition, true, checks) {
+                let guard = Self {
+                    name,
+                    check,
+                    start,
+                    end,
+                };
+                guards.push(guard.until_assigned(&block));
+            }
+        }
+
+        guards
+    }
+
+    fn new(name: String, check: Cursor<'a>, region: &Cursor<'a>) -> Self {
+        let range = region.raw_cursor().node().byte_range();
+        let guard = Self {
+            name,
+            check,
+            start: range.start,
+            end: range.end,
+        };
+        guard.until_assigned(region)
+    }
+
+    /// the check no longer holds once the variable is assigned again
+    fn until_assigned(mut self, region: &Cursor<'a>) -> Self {
+        for motion in region.traverse() {
+            if let Order::Enter(cur) = motion {
+                let byte = cur.raw_cursor().node().start_byte();
+                if byte < self.start || byte >= self.end {
+                    continue;
+                }
+                let mut left = cur.clone();
+                if matches!(
+                    cur.kind(),
+                    "assignment_expression"
+                        | "augmented_assignment_expression"
+                        | "reference_assignment_expression"
+                ) && left.goto_field("left")
+                    && left.kind() == "variable_name"
+                    && left.name().as_ref() == Some(&self.name)
+                {
+                    self.end = byte;
+                    break;
+                }
+            }
+        }
+        self
+    }
+
+    /// check applying to this variable
+    pub fn covers(&self, cursor: &Cursor<'a>) -> bool {
+        let byte = cursor.raw_cursor().node().start_byte();
+        cursor.filename() == self.check.filename()
+            && self.start <= byte
+            && byte < self.end
+            && cursor.name().as_ref() == Some(&self.name)
+    }
+
+    /// the body of an if statement always leaves the enclosing block
+    pub fn exits_early(cursor: Cursor<'a>) -> bool {
+        let mut body = cursor;
+        body.goto_field("body") && Self::exits(body)
+    }
+
+    /// the call doing the check, goes in the path like a sanitizer
+    pub fn check(&self) -> Cursor<'a> {
+        self.check.clone()
+    }
+
+    /// variables checked when the condition is true, or false if negated
+    fn conditions(
+        cursor: Cursor<'a>,
+        negated: bool,
+        checks: &HashMap<String, Option<Vec<u32>>>,
+    ) -> Vec<(String, Cursor<'a>)> {
+        let mut found = Vec::new();
+        match cursor.kind() {
+            "parenthesized_expression" => {
+                let mut inner = cursor;
+                if inner.goto_first_child() {
+                    while !inner.raw_cursor().node().is_named() && inner.goto_next_sibling() {}
+                    found.extend(Self::conditions(inner, negated, checks));
+                }
+            }
+            "unary_op_expression" => {
+                let mut operand = cursor.clone();
+                let mut not = false;
+                if operand.goto_first_child() {
+                    loop {
+                        if operand.to_str() == "!" {
+                            not = true;
+                        }
+                        if !operand.goto_next_sibling() {
+                            break;
+                        }
+                    }
+                }
+                if not {
+                    found.extend(Self::conditions(operand, !negated, checks));
+                }
+            }
+            "binary_expression" => {
+                let mut op = cursor.clone();
+                if !op.goto_field("operator") {
+                    return found;
+                }
+                // a && b holds both, !(a || b) fails both
+                let both = match op.to_str() {
+                    "&&" | "and" => !negated,
+                    "||" | "or" => negated,
+                    _ => false,
+                };
+                let (mut left, mut right) = (cursor.clone(), cursor);
+                if both && left.goto_field("left") && right.goto_field("right") {
+                    found.extend(Self::conditions(left, negated, checks));
+                    found.extend(Self::conditions(right, negated, checks));
+                }
+            }
+            "function_call_expression" if !negated => {
+                let name = cursor.name().unwrap_or_default();
+                if let Some(indices) = checks.get(&name) {
+                    for (i, arg) in Self::arguments(cursor.clone()).into_iter().enumerate() {
+                        let checked = match indices {
+                            Some(indices) => indices.contains(&(i as u32)),
+                            None => true,
+                        };
+                        if checked && arg.kind() == "variable_name" {
+                            if let Some(var) = arg.name() {
+                                found.push((var, cursor.clone()));
+                            }
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+        found
+    }
+
+    /// expressions passed to a call
+    fn arguments(cursor: Cursor<'a>) -> Vec<Cursor<'a>> {
+        let mut args = Vec::new();
+        let mut arg = cursor;
+        if !arg.goto_field("arguments") || !arg.goto_first_child() {
+            return args;
+        }
+        loop {
+            if arg.kind() == "argument" {
+                let mut value = arg.clone();
+                value.goto_first_child();
+                while !value.raw_cursor().node().is_named() && value.goto_next_sibling() {}
+                args.push(value);
+            }
+            if !arg.goto_next_sibling() {
+                return args;
+            }
+        }
+    }
+
+    /// statement always leaves the enclosing block
+    fn exits(cursor: Cursor<'a>) -> bool {
+        match cursor.kind() {
+            // the last statement of a block decides
+            "compound_statement" | "colon_block" => {
+                let mut last = None;
+                let mut stmt = cursor;
+                if stmt.goto_first_child() {
+                    loop {
+                        if stmt.raw_cursor().node().is_named() && stmt.kind() != "comment" {
+                            last = Some(stmt.clone());
+                        }
+                        if !stmt.goto_next_sibling() {
+                            break;
+                        }
+                    }
+                }
+                last.is_some_and(Self::exits)
+            }
+            "expression_statement" => {
+                let mut expr = cursor;
+                expr.goto_first_child() && Self::exits(expr)
+            }
+            "function_call_expression" => {
+                EXIT_CALLS.contains(&cursor.name().unwrap_or_default().as_str())
+            }
+            kind => EXITS.contains(&kind),
+        }
+    }
+}
@@ -1,2 +1,8 @@
+#[allow(clippy::module_inception)]
 pub mod analyzer;
+pub mod constants;
+pub mod fixtures;
+pub mod guard;
+pub mod ruletest;
+pub mod summary;
 pub mod taint;
@@ -0,0 +1,108 @@
+use crate::analyzer::analyzer::*;
+use crate::graph::rules::*;
+use crate::tree::file::*;
+
+/// a snippet from the rules that did not do what it said it would
+pub struct Failure {
+    pub vuln: String,
+    pub name: String,
+    pub snippet: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "FAIL {}: {}", self.name, self.message)?;
+        for line in self.snippet.lines() {
+            writeln!(f, "    {}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// run the vulnerable and safe snippets of every vuln, returns the number run and what failed
+pub fn run(rules: &Rules) -> (usize, Vec<Failure>) {
+    let mut count = 0;
+    let mut failures = Vec::new();
+
+    let mut names: Vec<&String> = rules.vulns().keys().collect();
+    names.sort();
+    for vuln in names {
+        let tests = rules.vulns()[vuln].tests();
+        let cases = tests
+            .vulnerable
+            .iter()
+            .enumerate()
+            .map(|(i, snippet)| (true, i, snippet))
+            .chain(
+                tests
+                    .safe
+                    .iter()
+                    .enumerate()
+                    .map(|(i, snippet)| (false, i, snippet)),
+            );
+
+        for (vulnerable, i, snippet) in cases {
+            count += 1;
+            let label = match vulnerable {
+                true => "vulnerable",
+                false => "safe",
+            };
+            let name = format!("{} {} #{}", vuln, label, i + 1);
+            let mut fail = |message: String| {
+                failures.push(Failure {
+                    vuln: vuln.clone(),
+                    name: name.clone(),
+                    snippet: snippet.clone(),
+                    message,
+                })
+            };
+
+            // snippets can leave out the open tag
+            let source = match snippet.trim_start().starts_with("<?") {
+                true => snippet.clone(),
+                false => format!("<?php\n{}", snippet),
+            };
+            let file = match File::from_source(&name, source) {
+                Ok(file) => file,
+                Err(e) => {
+                    fail(format!("does not parse: {}", e));
+                    continue;
+                }
+            };
+            if let Some(error) = file.parse_errors().first() {
+                fail(format!("does not parse: {}", error));
+                continue;
+            }
+
+            let mut analyzer = Analyzer::new(vec![&file], rules);
+            let found = analyzer
+                .graph()
+                .match_rules(rules)
+                .iter()
+                .filter(|finding| finding.vuln() == vuln)
+                .count();
+            match (vulnerable, found) {
+                (true, 0) => fail(format!("expected {}, found nothing", vuln)),
+                (false, n) if n > 0 => fail(format!("expected no {}, found {}", vuln, n)),
+                _ => (),
+            }
+        }
+    }
+
+    (count, failures)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_rules_pass_their_snippets() {
+        let rules = Rules::from_yaml("default").unwrap();
+        let (count, failures) = run(&rules);
+        let failures: Vec<String> = failures.iter().map(|f| f.to_string()).collect();
+        assert!(count > 0);
+        assert!(failures.is_empty(), "{}", failures.concat());
+    }
+}
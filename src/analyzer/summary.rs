@@ -0,0 +1,66 @@
+use crate::analyzer::taint::Taint;
+use crate::tree::cursor::*;
+
+/// what calling a function with a tainted param (or none) does,
+/// sinks and sanitizers inside the body stay in the graph behind the param vertex,
+/// returned values are copied to each call site so callers dont share them
+#[derive(Clone, Debug)]
+pub struct Summary<'a> {
+    // false while the body is still being analyzed
+    complete: bool,
+    // how tainted values reach the return statements
+    flows: Vec<Flow<'a>>,
+    // nonce and capability checks the function makes
+    checks: Vec<String>,
+}
+
+/// where a returned value comes from
+#[derive(Clone, Debug)]
+pub enum Origin<'a> {
+    // the tainted param, whatever the call site passed in
+    Param,
+    // a source read inside the function
+    Source(Taint),
+    // a vertex outside the function, like a captured variable
+    Vertex(Cursor<'a>),
+}
+
+/// one way a value gets from its origin to a return statement,
+/// path holds the steps in between so sanitizers on the way still count
+#[derive(Clone, Debug)]
+pub struct Flow<'a> {
+    pub origin: Origin<'a>,
+    pub path: Vec<Cursor<'a>>,
+}
+
+impl<'a> Summary<'a> {
+    /// placeholder for a function we are inside of
+    pub fn pending() -> Self {
+        Self {
+            complete: false,
+            flows: Vec::new(),
+            checks: Vec::new(),
+        }
+    }
+
+    pub fn new(flows: Vec<Flow<'a>>, checks: Vec<String>) -> Self {
+        Self {
+            complete: true,
+            flows,
+            checks,
+        }
+    }
+
+    /// recursive calls see an incomplete summary
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    pub fn flows(&self) -> &Vec<Flow<'a>> {
+        &self.flows
+    }
+
+    pub fn checks(&self) -> &Vec<String> {
+        &self.checks
+    }
+}
@@ -1,6 +1,8 @@
 use std::collections::HashSet;
 
 use crate::tree::cursor::*;
+use crate::tree::resolved::Resolved;
+use crate::utils::glob::*;
 
 #[derive(Clone, Debug, Hash, Eq, PartialEq)]
 pub enum TaintKind {
@@ -19,7 +21,7 @@ pub struct Taint {
 }
 
 impl Taint {
-    pub fn new(cursor: Cursor, kind: TaintKind) -> Result<Self, &str> {
+    pub fn new(cursor: Cursor<'_>, kind: TaintKind) -> Result<Self, &str> {
         if let Some(name) = cursor.name() {
             match kind {
                 TaintKind::Return => Ok(Taint::new_variable(cursor)),
@@ -30,14 +32,16 @@ impl Taint {
                 }),
             }
         } else {
-            return Err("unnamed taint");
+            Err("unnamed taint")
         }
     }
 
-    pub fn from_trace(cursor: Cursor) -> Result<Self, &str> {
+    pub fn from_trace(cursor: Cursor<'_>) -> Result<Self, &str> {
         match cursor.kind() {
             "return_statement" => Ok(Taint::new_return(cursor)),
-            "assignment_expression" => Ok(Taint::new_variable(cursor)),
+            "assignment_expression" | "augmented_assignment_expression" => {
+                Ok(Taint::new_variable(cursor))
+            }
             _ => Err("not a valid trace taint"),
         }
     }
@@ -58,14 +62,23 @@ impl Taint {
         }
     }
 
+    /// value returned by the function containing cursor
     pub fn new_return(cursor: Cursor) -> Self {
+        let scope = Scope::new(cursor);
         Self {
             kind: TaintKind::Return,
-            name: format!(
-                "return {}",
-                Scope::new(cursor.clone()).function.unwrap_or_default()
-            ),
-            scope: Scope::new_global(),
+            name: format!("return {}", scope.function.clone().unwrap_or_default()),
+            scope,
+        }
+    }
+
+    /// value a call returns at this call site
+    pub fn new_call_return(cursor: Cursor) -> Self {
+        let pos = cursor.raw_cursor().node().start_position();
+        Self {
+            kind: TaintKind::Return,
+            name: format!("return {}:{}", pos.row + 1, pos.column),
+            scope: Scope::new(cursor),
         }
     }
 
@@ -84,6 +97,12 @@ pub struct TaintList {
     list: HashSet<Taint>,
 }
 
+impl Default for TaintList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TaintList {
     pub fn new() -> Self {
         Self {
@@ -100,22 +119,22 @@ impl TaintList {
     }
 
     pub fn get(&self, taint: &Taint) -> Option<Taint> {
+        let mut found = None;
         for t in self.list.iter() {
-            if t.name == taint.name {
-                return Some(t.clone());
+            // source patterns like _* taint each variable they match under its own name
+            if t.kind == TaintKind::Source && is_glob(&t.name) && glob(&t.name, &taint.name) {
+                found = found.or_else(|| Some(Taint::new_source(taint.name.clone())));
+                continue;
             }
-        }
-        None
-    }
-
-    pub fn returns(&self) -> Vec<Taint> {
-        let mut vec = Vec::new();
-        for t in self.list.iter() {
-            if t.kind == TaintKind::Return {
-                vec.push(t.clone());
+            if t.name == taint.name {
+                // prefer the taint visible from the requested scope
+                if taint.scope.contains(&t.scope) {
+                    return Some(t.clone());
+                }
+                found = Some(t.clone());
             }
         }
-        vec
+        found
     }
 
     pub fn contains(&self, taint: &Taint) -> bool {
@@ -141,8 +160,10 @@ impl TaintList {
         self.list.retain(|sc| &sc.scope != scope);
     }
 
-    pub fn clear_returns(&mut self) {
-        self.list.retain(|sc| sc.kind != TaintKind::Return);
+    /// clear taints of top level code, keeping sources
+    pub fn clear_globals(&mut self) {
+        self.list
+            .retain(|sc| sc.kind == TaintKind::Source || !sc.scope.is_toplevel());
     }
 }
 
@@ -159,10 +180,17 @@ impl Scope {
         s.filename = Some(cursor.filename());
 
         for cur in cursor.trace() {
+            // innermost function wins
+            if s.function.is_some() {
+                break;
+            }
             match cur.kind() {
                 "method_declaration" | "function_definition" => {
                     s.function = cur.name();
                 }
+                "anonymous_function_creation_expression" | "arrow_function" => {
+                    s.function = Some(Resolved::closure_name(&cur));
+                }
                 _ => (),
             }
         }
@@ -178,15 +206,22 @@ impl Scope {
         }
     }
 
+    /// top level code of some file
+    pub fn is_toplevel(&self) -> bool {
+        self.filename.is_some() && self.function.is_none() && self.class.is_none()
+    }
+
     pub fn contains(&self, other: &Self) -> bool {
-        if let Some(_) = other.filename {
-            if self.filename == other.filename {
-                if self.class == other.class {
-                    if self.function == other.function {
-                        return true;
-                    }
-                }
-            } else {
+        // top level code shares one global scope across included files
+        if self.is_toplevel() && other.is_toplevel() {
+            return true;
+        }
+        if other.filename.is_some() {
+            if self.filename == other.filename
+                && self.class == other.class
+                && self.function == other.function
+            {
+                return true;
             }
         } else {
             return true;
@@ -208,31 +243,116 @@ impl Context {
     }
 }
 
+/// externally reachable code an analysis started from
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+pub struct EntryPoint {
+    // file, ajax, rest, shortcode, admin, cron...
+    pub kind: String,
+    // file name, hook name or route
+    pub name: String,
+    // who can reach it, nopriv, authenticated, admin...
+    pub privilege: String,
+}
+
+impl EntryPoint {
+    pub fn new(kind: String, name: String, privilege: String) -> Self {
+        Self {
+            kind,
+            name,
+            privilege,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Hash, Eq, PartialEq)]
 pub struct ContextStack {
     stack: Vec<Context>,
+    entry: Option<EntryPoint>,
+    // nonce and capability checks passed so far, with the depth they were made at
+    checks: Vec<(String, usize)>,
+}
+
+impl Default for ContextStack {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ContextStack {
     pub fn new() -> Self {
-        Self { stack: Vec::new() }
+        Self {
+            stack: Vec::new(),
+            entry: None,
+            checks: Vec::new(),
+        }
+    }
+
+    /// fresh stack for analyzing an entry point
+    pub fn new_entry(entry: EntryPoint) -> Self {
+        Self {
+            stack: Vec::new(),
+            entry: Some(entry),
+            checks: Vec::new(),
+        }
+    }
+
+    pub fn entry(&self) -> Option<&EntryPoint> {
+        self.entry.as_ref()
+    }
+
+    /// record a check like wp_verify_nonce at the current depth
+    pub fn check(&mut self, name: String) {
+        if !self.has_check(&name) {
+            self.checks.push((name, self.stack.len()));
+        }
+    }
+
+    pub fn has_check(&self, name: &str) -> bool {
+        self.checks.iter().any(|(check, _)| check == name)
+    }
+
+    pub fn checks(&self) -> Vec<String> {
+        self.checks.iter().map(|(check, _)| check.clone()).collect()
     }
 
-    /// returns false if recursive
+    /// returns false if recursive, without pushing
     pub fn push(&mut self, context: Context) -> bool {
-        for ctx in self.stack.iter() {
-            if ctx.eq(&context) {
-                self.stack
-                    .push(Context::new("".to_string(), "".to_string()));
-                return false;
-            }
+        if self.stack.contains(&context) {
+            return false;
         }
         self.stack.push(context);
         true
     }
 
+    /// push even if already inside this context
+    pub fn enter(&mut self, context: Context) {
+        self.stack.push(context);
+    }
+
+    /// leave a function or file, checks made inside still hold after it
     pub fn pop(&mut self) -> Option<Context> {
-        self.stack.pop()
+        let context = self.stack.pop();
+        self.settle(true);
+        context
+    }
+
+    /// leave a branch, checks made inside only hold after it if it exits early
+    pub fn pop_branch(&mut self, keep: bool) -> Option<Context> {
+        let context = self.stack.pop();
+        self.settle(keep);
+        context
+    }
+
+    fn settle(&mut self, keep: bool) {
+        let depth = self.stack.len();
+        match keep {
+            true => {
+                for (_, at) in self.checks.iter_mut() {
+                    *at = (*at).min(depth);
+                }
+            }
+            false => self.checks.retain(|(_, at)| *at <= depth),
+        }
     }
 
     pub fn contains(&self, other: &Self) -> bool {
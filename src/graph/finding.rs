@@ -0,0 +1,175 @@
+use super::rules::Metadata;
+use crate::analyzer::taint::EntryPoint;
+use crate::tree::cursor::*;
+use crate::tree::output::OutputContext;
+use std::collections::BTreeSet;
+
+/// how much to trust a finding
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, PartialOrd, Ord)]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+impl Confidence {
+    fn from_score(score: u32) -> Self {
+        match score {
+            80.. => Confidence::High,
+            60..=79 => Confidence::Medium,
+            _ => Confidence::Low,
+        }
+    }
+}
+
+impl std::fmt::Display for Confidence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Confidence::Low => write!(f, "low"),
+            Confidence::Medium => write!(f, "medium"),
+            Confidence::High => write!(f, "high"),
+        }
+    }
+}
+
+impl std::str::FromStr for Confidence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "low" => Ok(Confidence::Low),
+            "medium" => Ok(Confidence::Medium),
+            "high" => Ok(Confidence::High),
+            _ => Err(format!("unknown confidence {}, use low, medium or high", s)),
+        }
+    }
+}
+
+/// a guess the analysis made somewhere along a path
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, PartialOrd, Ord)]
+pub enum Approximation {
+    // the sink only matched by method name
+    NameOnlySink,
+    // a source only matched by method name
+    NameOnlySource,
+    // a method call resolved by name because the receiver is unknown
+    UnresolvedMethod,
+    // a function called through a variable, $fn($x)
+    DynamicCallable,
+    // a recursive call used what its function was known to do so far
+    RecursionCutoff,
+    // part of the path is in code that failed to parse
+    ParseError,
+}
+
+impl Approximation {
+    /// how much the guess takes off a perfect score of 100
+    fn penalty(&self) -> u32 {
+        match self {
+            Approximation::NameOnlySink | Approximation::NameOnlySource => 50,
+            Approximation::UnresolvedMethod | Approximation::ParseError => 30,
+            Approximation::DynamicCallable | Approximation::RecursionCutoff => 25,
+        }
+    }
+}
+
+impl std::fmt::Display for Approximation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Approximation::NameOnlySink => write!(f, "sink matched by name only"),
+            Approximation::NameOnlySource => write!(f, "source matched by name only"),
+            Approximation::UnresolvedMethod => write!(f, "method resolved by name only"),
+            Approximation::DynamicCallable => write!(f, "dynamic callable"),
+            Approximation::RecursionCutoff => write!(f, "recursion cut off"),
+            Approximation::ParseError => write!(f, "path through a parse error"),
+        }
+    }
+}
+
+/// a path from a sink back to a source that matches a vulnerability rule
+#[derive(Clone, Hash, Eq, PartialEq)]
+pub struct Finding<'a> {
+    vuln: String,
+    path: Vec<Cursor<'a>>,
+    entry: Option<EntryPoint>,
+    approximations: BTreeSet<Approximation>,
+    metadata: Metadata,
+    // where in the page the sink writes, for vulns with context escapers
+    context: Option<OutputContext>,
+    // escaper on the path that is meant for another context
+    mismatch: Option<String>,
+}
+
+impl<'a> Finding<'a> {
+    pub fn new(
+        vuln: String,
+        path: Vec<Cursor<'a>>,
+        entry: Option<EntryPoint>,
+        approximations: BTreeSet<Approximation>,
+        metadata: Metadata,
+        context: Option<OutputContext>,
+        mismatch: Option<String>,
+    ) -> Self {
+        Self {
+            vuln,
+            path,
+            entry,
+            approximations,
+            metadata,
+            context,
+            mismatch,
+        }
+    }
+
+    /// name of the matched vulnerability rule
+    pub fn vuln(&self) -> &str {
+        &self.vuln
+    }
+
+    /// vertices from the sink back to the source
+    pub fn path(&self) -> &Vec<Cursor<'a>> {
+        &self.path
+    }
+
+    /// 100 for a path without guesses, less for each kind of guess along it
+    pub fn score(&self) -> u32 {
+        let penalty: u32 = self.approximations.iter().map(|a| a.penalty()).sum();
+        100u32.saturating_sub(penalty)
+    }
+
+    pub fn confidence(&self) -> Confidence {
+        Confidence::from_score(self.score())
+    }
+
+    /// guesses made along the path
+    pub fn approximations(&self) -> &BTreeSet<Approximation> {
+        &self.approximations
+    }
+
+    /// severity, cwe and how to fix it, from the rules
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// output context of the sink, html, attribute, url or script
+    pub fn context(&self) -> Option<OutputContext> {
+        self.context
+    }
+
+    /// escaper used on the way that does not escape for the sink's context
+    pub fn mismatch(&self) -> Option<&str> {
+        self.mismatch.as_deref()
+    }
+
+    /// entry point the sink was reached from
+    pub fn entry(&self) -> Option<&EntryPoint> {
+        self.entry.as_ref()
+    }
+
+    pub fn filename(&self) -> String {
+        match self.path.first() {
+            Some(cur) => cur.filename(),
+            None => String::new(),
+        }
+    }
+}
@@ -1,14 +1,26 @@
+use super::finding::{Approximation, Finding};
 use super::rules::{Rules, VertKind, Vuln};
 use super::vertex::*;
+use crate::analyzer::summary::{Flow, Origin};
 use crate::analyzer::taint::*;
 use crate::tree::cursor::*;
+use crate::tree::output::OutputContext;
+use crate::tree::receiver::Match;
 use crate::tree::resolved::Resolved;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 pub struct Graph<'a> {
     nodes: HashMap<Cursor<'a>, Vertex<'a>>,
     leaves: HashMap<Taint, Vec<Cursor<'a>>>,
     returns: HashMap<Resolved<'a>, Vec<Cursor<'a>>>,
+    // guesses the analyzer made at calls, lower the confidence of paths through them
+    approximations: HashMap<Cursor<'a>, HashSet<Approximation>>,
+}
+
+impl<'a> Default for Graph<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<'a> Graph<'a> {
@@ -17,9 +29,18 @@ impl<'a> Graph<'a> {
             nodes: HashMap::new(),
             leaves: HashMap::new(),
             returns: HashMap::new(),
+            approximations: HashMap::new(),
         }
     }
 
+    /// remember a guess made at a cursor
+    pub fn approximate(&mut self, cursor: Cursor<'a>, approximation: Approximation) {
+        self.approximations
+            .entry(cursor)
+            .or_default()
+            .insert(approximation);
+    }
+
     pub fn returns(&self, resolved: &Resolved<'a>) -> Option<&Vec<Cursor<'a>>> {
         self.returns.get(resolved)
     }
@@ -27,11 +48,16 @@ impl<'a> Graph<'a> {
     /// push a taint to the graph, returns false if recursive
     pub fn push(&mut self, path: PathItem<'a>, cursor: Cursor<'a>, vertex: Vertex<'a>) -> bool {
         let mut vertex = vertex;
+        // functions run once per entry point, their edges remember which one
+        let path = path.within(vertex.context().clone());
         let known = self.nodes.contains_key(&cursor);
 
         match known {
             true => {
-                self.add_edges(path, &mut vertex);
+                // another flow into a vertex we already have
+                if let Some(known) = self.nodes.get_mut(&cursor) {
+                    Self::connect(&self.leaves, path, known);
+                }
                 self.update_leaves(cursor, vertex)
             }
             false => {
@@ -44,17 +70,42 @@ impl<'a> Graph<'a> {
         !known
     }
 
-    pub fn match_rules(&self, ruleset: &Rules) -> HashSet<Vec<Cursor>> {
+    pub fn match_rules(&self, ruleset: &Rules) -> HashSet<Finding<'a>> {
         let mut results = HashSet::new();
 
         for (k, v) in self.nodes.iter() {
             for (_, path) in v.parents().iter().chain(v.sources().iter()) {
+                // checks and entry point of the run that reached the sink
+                let context = path.context().unwrap_or(v.context());
+                let entry = context.entry();
                 for segment in path.segments() {
-                    let name = segment.name().unwrap_or_default();
-                    let kind = segment.kind().to_string();
-                    for (_, vuln) in ruleset.vulns().iter() {
-                        if vuln.has_sink(&name) || vuln.has_sink(&kind) {
-                            results.extend(self.crawl(&vuln, vec![k.clone()]));
+                    for (vuln_name, vuln) in ruleset.vulns().iter() {
+                        if vuln.is_checked(context) {
+                            continue;
+                        }
+                        if let Some((how, sink)) = vuln.sink(segment.clone()) {
+                            // where in the page the sink writes, for escapers of one context
+                            let output = match vuln.has_contexts() {
+                                true => Some(OutputContext::of(segment)),
+                                false => None,
+                            };
+                            for path in self.crawl(vuln, output, entry, vec![k.clone()]) {
+                                let mut approximations = self.approximations(vuln, entry, &path);
+                                if how == Match::NameOnly {
+                                    approximations.insert(Approximation::NameOnlySink);
+                                }
+                                let mismatch =
+                                    output.and_then(|c| self.mismatch(vuln, c, entry, &path));
+                                results.insert(Finding::new(
+                                    vuln_name.clone(),
+                                    path,
+                                    entry.cloned(),
+                                    approximations,
+                                    vuln.metadata(&sink),
+                                    output,
+                                    mismatch,
+                                ));
+                            }
                         }
                     }
                 }
@@ -64,26 +115,227 @@ impl<'a> Graph<'a> {
         results
     }
 
+    /// the entry point to keep following from an edge, None once any will do,
+    /// or no edge if it was taken from another entry point
+    fn follow<'e>(
+        entry: Option<&'e EntryPoint>,
+        edge: &'e PathItem<'a>,
+    ) -> Option<Option<&'e EntryPoint>> {
+        match edge.context() {
+            Some(context) if entry.is_some() && context.entry() != entry => None,
+            Some(_) => Some(entry),
+            // edges added outside of a run belong to no entry point
+            None => Some(None),
+        }
+    }
+
+    /// guesses made on the edges of a path from a sink back to its source
+    fn approximations(
+        &self,
+        vuln: &Vuln,
+        entry: Option<&EntryPoint>,
+        path: &[Cursor<'a>],
+    ) -> BTreeSet<Approximation> {
+        let mut found = BTreeSet::new();
+        for cursor in path.iter() {
+            if Self::in_error(cursor) {
+                found.insert(Approximation::ParseError);
+            }
+        }
+        for (last, edge) in self.edges(vuln, entry, path) {
+            for segment in edge.segments() {
+                if let Some(approximations) = self.approximations.get(segment) {
+                    found.extend(approximations.iter().copied());
+                }
+            }
+            if !last {
+                continue;
+            }
+            // sources start below the first step of their edge, $id = $request->get_param()
+            if let Some(first) = edge.segments().next() {
+                for (at, approximations) in self.approximations.iter() {
+                    if Self::contains(first, at) {
+                        found.extend(approximations.iter().copied());
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    /// escaper on a path that is meant for another output context, esc_html in an href
+    fn mismatch(
+        &self,
+        vuln: &Vuln,
+        context: OutputContext,
+        entry: Option<&EntryPoint>,
+        path: &[Cursor<'a>],
+    ) -> Option<String> {
+        for (_, edge) in self.edges(vuln, entry, path) {
+            for segment in edge.segments() {
+                if vuln.identify(segment.clone()) == Some(VertKind::Sanitizer)
+                    && !vuln.escapes(segment.clone(), context)
+                {
+                    return segment.name();
+                }
+            }
+        }
+        None
+    }
+
+    /// edges between the vertices of a path, and whether each comes from the source end
+    fn edges(
+        &self,
+        vuln: &Vuln,
+        entry: Option<&EntryPoint>,
+        path: &[Cursor<'a>],
+    ) -> Vec<(bool, &PathItem<'a>)> {
+        let mut edges = Vec::new();
+        let mut entry = entry;
+        for (i, cursor) in path.iter().enumerate() {
+            let vert = match self.nodes.get(cursor) {
+                Some(vert) => vert,
+                None => continue,
+            };
+            let last = i + 1 == path.len();
+            let mut next = entry;
+            for (parent, edge) in vert.parents().iter() {
+                if path.get(i + 1) != Some(parent)
+                    && !(last && vuln.has_source(&edge.source().name))
+                {
+                    continue;
+                }
+                if let Some(follow) = Self::follow(entry, edge) {
+                    // past a link between stored data any entry point will do
+                    if follow.is_none() {
+                        next = None;
+                    }
+                    edges.push((last, edge));
+                }
+            }
+            if last {
+                for (_, edge) in vert.sources().iter() {
+                    if Self::follow(entry, edge).is_some() {
+                        edges.push((last, edge));
+                    }
+                }
+            }
+            entry = next;
+        }
+        edges
+    }
+
+    /// how the value at a return vertex got there, walking up to the tainted param,
+    /// to sources and to vertices outside the function. other params end the walk,
+    /// their callers are not this call's business
+    pub fn flows(
+        &self,
+        ret: &Cursor<'a>,
+        param: Option<&Cursor<'a>>,
+        function: &Cursor<'a>,
+    ) -> Vec<Flow<'a>> {
+        let mut flows = Vec::new();
+        let mut seen = vec![ret.clone()];
+        self.walk(ret, param, function, Vec::new(), &mut seen, &mut flows);
+        flows
+    }
+
+    fn walk(
+        &self,
+        at: &Cursor<'a>,
+        param: Option<&Cursor<'a>>,
+        function: &Cursor<'a>,
+        path: Vec<Cursor<'a>>,
+        seen: &mut Vec<Cursor<'a>>,
+        flows: &mut Vec<Flow<'a>>,
+    ) {
+        if Some(at) == param {
+            flows.push(Flow {
+                origin: Origin::Param,
+                path,
+            });
+            return;
+        }
+        if !Self::contains(function, at) {
+            flows.push(Flow {
+                origin: Origin::Vertex(at.clone()),
+                path,
+            });
+            return;
+        }
+        let vert = match self.nodes.get(at) {
+            Some(vert) => vert,
+            None => return,
+        };
+        for (parent, edge) in vert.parents().iter() {
+            if seen.contains(parent) || (Self::is_param(function, parent) && Some(parent) != param)
+            {
+                continue;
+            }
+            let mut path = path.clone();
+            path.extend(edge.segments().cloned());
+            seen.push(parent.clone());
+            self.walk(parent, param, function, path, seen, flows);
+            seen.pop();
+        }
+        for (_, edge) in vert.sources().iter() {
+            let mut path = path.clone();
+            path.extend(edge.segments().cloned());
+            flows.push(Flow {
+                origin: Origin::Source(edge.source().clone()),
+                path,
+            });
+        }
+    }
+
+    /// cursor is in the parameter list of a function
+    fn is_param(function: &Cursor<'a>, cursor: &Cursor<'a>) -> bool {
+        let mut params = function.clone();
+        params.goto_field("parameters") && Self::contains(&params, cursor)
+    }
+
+    /// cursor is inside or around code tree-sitter could not parse
+    fn in_error(cursor: &Cursor<'a>) -> bool {
+        if cursor.raw_cursor().node().has_error() {
+            return true;
+        }
+        let mut cur = cursor.clone();
+        loop {
+            if cur.kind() == "ERROR" {
+                return true;
+            }
+            if !cur.goto_parent() {
+                return false;
+            }
+        }
+    }
+
+    fn contains(outer: &Cursor<'a>, inner: &Cursor<'a>) -> bool {
+        let (outer_range, inner_range) = (
+            outer.raw_cursor().node().byte_range(),
+            inner.raw_cursor().node().byte_range(),
+        );
+        outer.filename() == inner.filename()
+            && outer_range.start <= inner_range.start
+            && inner_range.end <= outer_range.end
+    }
+
     /// dump graph into DOT format string
     pub fn dump(&self) -> String {
-        let mut s = format!("digraph {{\n");
-        let mut i = 0;
-        for (k, v) in self.nodes.iter() {
+        let mut s = "digraph {\n".to_string();
+        for (i, (k, v)) in self.nodes.iter().enumerate() {
             s.push_str(&format!(
                 "\t{i} [ label = \"{}\" ]\n",
                 k.to_str().replace("\"", "\\\"")
             ));
 
             for (parent, _path) in v.parents().iter() {
-                let mut j = 0;
-                for (k, _) in self.nodes.iter() {
+                for (j, (k, _)) in self.nodes.iter().enumerate() {
                     if parent == k {
                         s.push_str(&format!("\t{j} -> {i} []\n"));
                     }
-                    j += 1;
                 }
             }
-            i += 1;
         }
         s.push('}');
         s
@@ -99,22 +351,65 @@ impl<'a> Graph<'a> {
         }
     }
 
-    /// get rid of returns after using them, since they have global scope
-
-    //
-    // NEED TO IMPROVE THIS, SECOND CALL OF FUNC DOESNT RETURN TAINT
-    //
-    pub fn clear_returns(&mut self) {
+    /// forget leaves of top level code, before starting a new entry point
+    pub fn clear_globals(&mut self) {
         for (t, v) in self.leaves.iter_mut() {
-            if t.kind == TaintKind::Return {
+            if t.kind != TaintKind::Source && t.scope.is_toplevel() {
                 *v = Vec::new();
             }
         }
     }
 
+    /// connect reads of stored data to the tainted writes with the same store and key
+    pub fn link_stores(&mut self, writes: &[(String, Cursor<'a>)]) {
+        for vertex in self.nodes.values_mut() {
+            let reads: Vec<String> = vertex
+                .sources()
+                .iter()
+                .map(|(_, path)| path.source().name.clone())
+                .filter(|name| name.starts_with("stored "))
+                .collect();
+            for read in reads {
+                for (write, cursor) in writes.iter() {
+                    if Self::same_store(&read, write) {
+                        // the write is its own vertex, dont match it as a sink again here
+                        let path = PathItem::new(Taint::new_source(write.clone()), Vec::new());
+                        vertex.add_parent(cursor.clone(), path);
+                    }
+                }
+            }
+        }
+    }
+
+    /// stores match if the keys are equal or one of them is unknown
+    fn same_store(read: &str, write: &str) -> bool {
+        match (read.split_once(':'), write.split_once(':')) {
+            (Some((rs, rk)), Some((ws, wk))) => rs == ws && (rk == wk || rk == "*" || wk == "*"),
+            _ => false,
+        }
+    }
+
+    /// remove the vertices holding a taint, like the returns of a function
+    pub fn take_leaves(&mut self, taint: &Taint) -> Vec<Cursor<'a>> {
+        self.leaves.remove(taint).unwrap_or_default()
+    }
+
+    /// set the vertices holding a taint, like returns at a call site
+    pub fn set_leaves(&mut self, taint: Taint, leaves: Vec<Cursor<'a>>) {
+        self.leaves.insert(taint, leaves);
+    }
+
     /// connect a given vertex to the leaves
     fn add_edges(&mut self, path: PathItem<'a>, vertex: &mut Vertex<'a>) {
-        if let Some(leaves) = self.leaves.get(&path.source()) {
+        Self::connect(&self.leaves, path, vertex);
+    }
+
+    fn connect(
+        leaves: &HashMap<Taint, Vec<Cursor<'a>>>,
+        path: PathItem<'a>,
+        vertex: &mut Vertex<'a>,
+    ) {
+        if let Some(leaves) = leaves.get(path.source()) {
             for leaf in leaves {
                 vertex.add_parent(leaf.clone(), path.clone());
             }
@@ -129,11 +424,11 @@ impl<'a> Graph<'a> {
     /// add assigning leaf, and prune as needed
     fn update_leaves(&mut self, cursor: Cursor<'a>, vertex: Vertex<'a>) {
         if let Some(assign) = vertex.assign() {
-            if let Some(leaves) = self.leaves.get(&assign) {
+            if let Some(leaves) = self.leaves.get(assign) {
                 // variabe is already tainted, remove overwritten taints
                 let mut newvec = vec![cursor];
                 for leaf in leaves.iter() {
-                    if let Some(vert) = self.nodes.get(&leaf) {
+                    if let Some(vert) = self.nodes.get(leaf) {
                         if !vertex.context().contains(vert.context()) {
                             newvec.push(leaf.clone());
                         }
@@ -147,7 +442,14 @@ impl<'a> Graph<'a> {
         }
     }
 
-    fn crawl(&self, vuln: &Vuln, stack: Vec<Cursor<'a>>) -> HashSet<Vec<Cursor>> {
+    /// paths from a sink back to sources, escapers meant for another context dont stop them
+    fn crawl(
+        &self,
+        vuln: &Vuln,
+        context: Option<OutputContext>,
+        entry: Option<&EntryPoint>,
+        stack: Vec<Cursor<'a>>,
+    ) -> HashSet<Vec<Cursor<'a>>> {
         let mut results = HashSet::new();
         let mut stack = stack.clone();
 
@@ -155,10 +457,17 @@ impl<'a> Graph<'a> {
             if let Some(vert) = self.nodes.get(last) {
                 // crawl up each parent taint
                 for (parent, path) in vert.parents().iter() {
+                    // stay in the entry point the sink was reached from
+                    let entry = match Self::follow(entry, path) {
+                        Some(entry) => entry,
+                        None => continue,
+                    };
                     let mut sanitized = false;
                     for segment in path.segments() {
                         match vuln.identify(segment.clone()) {
-                            Some(VertKind::Sanitizer) => {
+                            Some(VertKind::Sanitizer)
+                                if context.is_none_or(|c| vuln.escapes(segment.clone(), c)) =>
+                            {
                                 sanitized = true;
                                 break;
                             }
@@ -172,17 +481,26 @@ impl<'a> Graph<'a> {
                         if vuln.has_source(&path.source().name) {
                             results.insert(stack.clone());
                         }
+                        // known vertices gain edges later on, so the graph can loop
+                        if stack.contains(parent) {
+                            continue;
+                        }
                         stack.push(parent.clone());
-                        results.extend(self.crawl(vuln, stack.clone()));
+                        results.extend(self.crawl(vuln, context, entry, stack.clone()));
                         stack.pop();
                     }
                 }
                 // crawl up each source taint
                 for (_, path) in vert.sources().iter() {
+                    if Self::follow(entry, path).is_none() {
+                        continue;
+                    }
                     let mut sanitized = false;
                     for segment in path.segments() {
                         match vuln.identify(segment.clone()) {
-                            Some(VertKind::Sanitizer) => {
+                            Some(VertKind::Sanitizer)
+                                if context.is_none_or(|c| vuln.escapes(segment.clone(), c)) =>
+                            {
                                 sanitized = true;
                                 break;
                             }
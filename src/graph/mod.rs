@@ -1,3 +1,6 @@
+pub mod finding;
+#[allow(clippy::module_inception)]
 pub mod graph;
 pub mod rules;
+pub mod validate;
 pub mod vertex;
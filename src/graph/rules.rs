@@ -1,17 +1,162 @@
+use super::validate::Diagnostic;
+use crate::analyzer::taint::ContextStack;
 use crate::tree::cursor::*;
+use crate::tree::output::OutputContext;
+use crate::tree::receiver::*;
+use crate::utils::glob::*;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::path::Path;
 
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+// the rules shipped with the scanner, used when there is no rule file
+// and by rule files that include "default"
+const DEFAULT_PACK: &str = include_str!("../../new.yaml");
+
+// numeric and boolean coercions, these sanitize every vuln unless it opts out
+const COERCIONS: [&str; 13] = [
+    "(int)",
+    "(integer)",
+    "(bool)",
+    "(boolean)",
+    "(float)",
+    "(double)",
+    "(real)",
+    "intval",
+    "absint",
+    "floatval",
+    "doubleval",
+    "boolval",
+    "settype",
+];
+
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Vuln {
-    sinks: HashMap<String, Option<Vec<u32>>>,
+    #[serde(default)]
+    sinks: HashMap<String, Option<Sink>>,
+    #[serde(default)]
     sources: HashSet<String>,
     // funcs that make sink safe
+    #[serde(default)]
     sanitizers: HashMap<String, Option<Vec<u32>>>,
+    // funcs that make a variable safe in the branch where they pass
+    #[serde(default)]
+    guards: HashMap<String, Option<Vec<u32>>>,
+    // sanitizers that only escape for some places in a page, html, attribute, url or script,
+    // a sanitizer listed under none of them escapes everywhere
+    #[serde(default)]
+    contexts: HashMap<String, HashSet<String>>,
     // funcs that make sink dangerous
     waypoints: Option<Vec<Waypoint>>,
+    // whether casts and typed params make data safe, defaults to true
+    coercion: Option<bool>,
+    // only alert when none of these nonce or capability checks came before the sink
+    #[serde(default)]
+    checks: HashSet<String>,
+    // entries to drop from an included vuln of the same name
+    #[serde(default, skip_serializing)]
+    remove: Removals,
+    // what to report with findings, sinks can override these
+    severity: Option<Severity>,
+    cwe: Option<u32>,
+    description: Option<String>,
+    remediation: Option<String>,
+    #[serde(default)]
+    references: Vec<String>,
+    // php snippets this vuln should and should not alert on, run by test-rules
+    #[serde(default)]
+    tests: Tests,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Tests {
+    #[serde(default)]
+    pub vulnerable: Vec<String>,
+    #[serde(default)]
+    pub safe: Vec<String>,
+}
+
+/// a sink is a list of dangerous arguments, or a map with report details,
+/// "system": { severity: "critical" }
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Sink {
+    Args(Vec<u32>),
+    Detailed(SinkDetails),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SinkDetails {
+    args: Option<Vec<u32>>,
+    severity: Option<Severity>,
+    cwe: Option<u32>,
+    description: Option<String>,
+    remediation: Option<String>,
+    #[serde(default)]
+    references: Vec<String>,
+}
+
+impl Sink {
+    /// arguments that make the sink dangerous, all of them if none
+    pub fn args(&self) -> Option<Vec<u32>> {
+        match self {
+            Sink::Args(args) => Some(args.clone()),
+            Sink::Detailed(details) => details.args.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
 }
 
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Low => write!(f, "low"),
+            Severity::Medium => write!(f, "medium"),
+            Severity::High => write!(f, "high"),
+            Severity::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+/// what a finding is reported with, from its vuln and the sink it reached
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct Metadata {
+    pub severity: Option<Severity>,
+    pub cwe: Option<u32>,
+    pub description: Option<String>,
+    pub remediation: Option<String>,
+    pub references: Vec<String>,
+}
+
+/// names to take out of an included vuln
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Removals {
+    #[serde(default)]
+    sinks: Vec<String>,
+    #[serde(default)]
+    sources: Vec<String>,
+    #[serde(default)]
+    sanitizers: Vec<String>,
+    #[serde(default)]
+    guards: Vec<String>,
+    #[serde(default)]
+    checks: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum VertKind {
     Source,
     Sanitizer,
@@ -19,31 +164,107 @@ pub enum VertKind {
 }
 
 impl Vuln {
-    /// standardize cursor to matching string
     pub fn identify(&self, cursor: Cursor) -> Option<VertKind> {
-        let kind = cursor.kind().to_string();
-        let name = match cursor.kind() {
-            "cast_type" => {
-                let s = format!("({})", cursor.to_str());
-                println!("cast type {s}");
-                s
-            }
-            _ => cursor.name().unwrap_or_default(),
+        self.classify(cursor).map(|(kind, _)| kind)
+    }
+
+    /// how a cursor matches the sinks and which sink rule it was, checked on its own
+    /// since sinks like include_expression contain the names of sources
+    pub fn sink(&self, cursor: Cursor) -> Option<(Match, String)> {
+        let (kind, call) = Self::call_name(cursor);
+        call.best_rule(self.sinks.keys(), &kind)
+            .map(|(how, rule)| (how, rule.clone()))
+    }
+
+    /// report details for a sink rule, the sink's own settings win over the vuln's
+    pub fn metadata(&self, sink: &str) -> Metadata {
+        let mut metadata = Metadata {
+            severity: self.severity,
+            cwe: self.cwe,
+            description: self.description.clone(),
+            remediation: self.remediation.clone(),
+            references: self.references.clone(),
         };
+        if let Some(Some(Sink::Detailed(details))) = self.sinks.get(sink) {
+            metadata.severity = details.severity.or(metadata.severity);
+            metadata.cwe = details.cwe.or(metadata.cwe);
+            metadata.description = details.description.clone().or(metadata.description);
+            metadata.remediation = details.remediation.clone().or(metadata.remediation);
+            metadata.references.extend(details.references.clone());
+        }
+        metadata
+    }
 
-        if self.has_sanitizer(&kind) || self.has_sanitizer(&name) {
-            return Some(VertKind::Sanitizer);
+    /// whether sinks of this vuln care where in the page they write
+    pub fn has_contexts(&self) -> bool {
+        !self.contexts.is_empty()
+    }
+
+    pub fn contexts(&self) -> &HashMap<String, HashSet<String>> {
+        &self.contexts
+    }
+
+    /// whether a sanitizer makes data safe in an output context, esc_html does not in a url
+    pub fn escapes(&self, cursor: Cursor, context: OutputContext) -> bool {
+        let (_, call) = Self::call_name(cursor);
+        let listed = |names: &HashSet<String>| names.iter().any(|name| glob(name, &call.name));
+        match self.contexts.get(&context.to_string()) {
+            Some(names) if listed(names) => true,
+            _ => !self.contexts.values().any(listed),
         }
+    }
 
-        if self.has_source(&kind) || self.has_source(&name) {
-            return Some(VertKind::Source);
+    /// standardize cursor to matching string, and say how sure the match is
+    pub fn classify(&self, cursor: Cursor) -> Option<(VertKind, Match)> {
+        let (kind, call) = Self::call_name(cursor);
+
+        if self.coercion.unwrap_or(true) && COERCIONS.contains(&call.name.as_str()) {
+            return Some((VertKind::Sanitizer, Match::Exact));
         }
 
-        if self.has_sink(&kind) || self.has_sink(&name) {
-            return Some(VertKind::Sink);
+        // the strongest match wins, an exact sink beats a sanitizer pattern,
+        // ties go to sanitizers then sources
+        let sanitizers = self.sanitizers.keys().chain(self.guards.keys());
+        let candidates = [
+            (VertKind::Sanitizer, call.best(sanitizers, &kind)),
+            (VertKind::Source, call.best(self.sources.iter(), &kind)),
+            (VertKind::Sink, call.best(self.sinks.keys(), &kind)),
+        ];
+        let mut best: Option<(VertKind, Match)> = None;
+        for (vert, how) in candidates {
+            if let Some(how) = how {
+                if best.is_none_or(|(_, b)| how > b) {
+                    best = Some((vert, how));
+                }
+            }
         }
+        best
+    }
 
-        None
+    /// standardize cursor to matching string,
+    /// rules can name a receiver, wpdb->query or Foo::bar
+    fn call_name(cursor: Cursor) -> (String, CallName) {
+        // a short echo tag prints like echo does, <?= $x ?>
+        if cursor.is_short_echo() {
+            let kind = "short_echo_statement".to_string();
+            return (kind.clone(), CallName::new(&cursor, kind));
+        }
+        let kind = cursor.kind().to_string();
+        let name = match cursor.kind() {
+            // callables given by name, array_map('esc_sql', ...)
+            "string" => cursor.string_value().unwrap_or_default(),
+            // casts and scalar param types, (int) $x or function f(?int $x)
+            "cast_type" | "primitive_type" | "optional_type" | "union_type" => format!(
+                "({})",
+                cursor
+                    .to_str()
+                    .trim_start_matches('?')
+                    .trim()
+                    .to_lowercase()
+            ),
+            _ => cursor.name().unwrap_or_default(),
+        };
+        (kind, CallName::new(&cursor, name))
     }
 
     pub fn sources(&self) -> &HashSet<String> {
@@ -52,9 +273,13 @@ impl Vuln {
 
     pub fn has_source(&self, source: &String) -> bool {
         self.sources.contains(source)
+            || self
+                .sources
+                .iter()
+                .any(|pattern| is_glob(pattern) && glob(pattern, source))
     }
 
-    pub fn sinks(&self) -> &HashMap<String, Option<Vec<u32>>> {
+    pub fn sinks(&self) -> &HashMap<String, Option<Sink>> {
         &self.sinks
     }
 
@@ -69,26 +294,168 @@ impl Vuln {
     pub fn has_sanitizer(&self, sanitizer: &String) -> bool {
         self.sanitizers.contains_key(sanitizer)
     }
+
+    pub fn has_guard(&self, guard: &String) -> bool {
+        self.guards.contains_key(guard)
+    }
+
+    pub fn tests(&self) -> &Tests {
+        &self.tests
+    }
+
+    /// a check this vuln requires to be missing was made
+    pub fn is_checked(&self, context: &ContextStack) -> bool {
+        self.checks.iter().any(|check| context.has_check(check))
+    }
+
+    /// layer another file's vuln on top, its removals go first
+    /// and its entries add to or override ours
+    fn merge(&mut self, other: Vuln) {
+        let remove = other.remove;
+        for name in remove.sinks.iter() {
+            self.sinks.remove(name);
+        }
+        for name in remove.sources.iter() {
+            self.sources.remove(name);
+        }
+        for name in remove.sanitizers.iter() {
+            self.sanitizers.remove(name);
+        }
+        for name in remove.guards.iter() {
+            self.guards.remove(name);
+        }
+        for name in remove.checks.iter() {
+            self.checks.remove(name);
+        }
+
+        self.sinks.extend(other.sinks);
+        self.sources.extend(other.sources);
+        self.sanitizers.extend(other.sanitizers);
+        self.guards.extend(other.guards);
+        self.checks.extend(other.checks);
+        for (context, names) in other.contexts {
+            self.contexts.entry(context).or_default().extend(names);
+        }
+        if other.waypoints.is_some() {
+            self.waypoints = other.waypoints;
+        }
+        if other.coercion.is_some() {
+            self.coercion = other.coercion;
+        }
+        self.severity = other.severity.or(self.severity);
+        self.cwe = other.cwe.or(self.cwe);
+        self.description = other.description.or(self.description.take());
+        self.remediation = other.remediation.or(self.remediation.take());
+        self.references.extend(other.references);
+        self.tests.vulnerable.extend(other.tests.vulnerable);
+        self.tests.safe.extend(other.tests.safe);
+    }
 }
 
 // a set of rules to alert for
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Rules {
+    // rule files to load first, relative to this one, "default" is the built in pack
+    #[serde(default, skip_serializing)]
+    include: Vec<String>,
     // sinks and their data
+    #[serde(default)]
     vulns: HashMap<String, Vuln>,
     // sources just to get the analyzer started
+    #[serde(default)]
     sources: HashSet<String>,
-    hooks: HashSet<String>,
+    // functions that register or run wordpress hooks
+    #[serde(default)]
+    hooks: HashMap<String, Hook>,
+    // functions that pass their arguments on to a callable
+    #[serde(default)]
+    callbacks: HashMap<String, Callback>,
+    // functions that store data and read it back
+    #[serde(default)]
+    storage: Storage,
 }
 
 impl Rules {
     pub fn from_yaml(filename: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        // parse yaml/json into our structure
-        let contents = std::fs::read_to_string(filename)?;
-        let rules: Self = serde_yaml::from_str(&contents)?;
+        let (rules, warnings) = Self::check(filename)?;
+        for warning in warnings {
+            eprintln!("{}", warning);
+        }
         Ok(rules)
     }
 
+    /// the rule file in the working directory, or the built in pack without one
+    pub fn load(filename: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        match Path::new(filename).exists() {
+            true => Self::from_yaml(filename),
+            false => Self::from_yaml("default"),
+        }
+    }
+
+    /// load a rule file and its includes and list what looks wrong in them,
+    /// unknown keys are errors
+    pub fn check(filename: &str) -> Result<(Self, Vec<Diagnostic>), Box<dyn std::error::Error>> {
+        let mut files = Vec::new();
+        let rules = Self::read(filename, &mut Vec::new(), &mut files)?;
+        let warnings = rules.validate(&files);
+        Ok((rules, warnings))
+    }
+
+    /// parse a file and merge it over its includes, files collects every file read
+    fn read(
+        filename: &str,
+        loading: &mut Vec<String>,
+        files: &mut Vec<(String, String)>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        if loading.iter().any(|f| f == filename) {
+            return Err(format!("{}: error: includes itself", filename).into());
+        }
+        // included twice through different files
+        if files.iter().any(|(f, _)| f == filename) {
+            return Ok(Self::default());
+        }
+
+        // parse yaml/json into our structure
+        let contents = match filename {
+            "default" => DEFAULT_PACK.to_string(),
+            _ => std::fs::read_to_string(filename)
+                .map_err(|e| format!("{}: error: {}", filename, e))?,
+        };
+        let mut rules: Self =
+            serde_yaml::from_str(&contents).map_err(|e| format!("{}: error: {}", filename, e))?;
+
+        let mut merged = Self::default();
+        loading.push(filename.to_string());
+        for include in std::mem::take(&mut rules.include) {
+            let path = match include.as_str() {
+                "default" => include,
+                _ => match Path::new(filename).parent() {
+                    Some(dir) => dir.join(&include).to_string_lossy().to_string(),
+                    None => include,
+                },
+            };
+            merged.merge(Self::read(&path, loading, files)?);
+        }
+        loading.pop();
+
+        merged.merge(rules);
+        files.push((filename.to_string(), contents));
+        Ok(merged)
+    }
+
+    /// layer another rule file on top of this one
+    fn merge(&mut self, other: Rules) {
+        for (name, vuln) in other.vulns {
+            self.vulns.entry(name).or_default().merge(vuln);
+        }
+        self.sources.extend(other.sources);
+        self.hooks.extend(other.hooks);
+        self.callbacks.extend(other.callbacks);
+        self.storage.writes.extend(other.storage.writes);
+        self.storage.reads.extend(other.storage.reads);
+    }
+
     pub fn sources(&self) -> HashSet<String> {
         let mut outset = self.sources.clone();
         for (_kind, vuln) in self.vulns.iter() {
@@ -98,31 +465,333 @@ impl Rules {
     }
 
     pub fn has_source(&self, source: &String) -> bool {
-        self.sources().contains(source)
+        let matches =
+            |pattern: &String| pattern == source || is_glob(pattern) && glob(pattern, source);
+        self.vulns.values().any(|vuln| vuln.has_source(source)) || self.sources.iter().any(matches)
     }
 
     pub fn sinks(&self) -> HashMap<String, Option<Vec<u32>>> {
         let mut names = HashMap::new();
         for (_kind, vuln) in self.vulns.iter() {
             for (name, sink) in vuln.sinks.iter() {
-                names.insert(name.clone(), sink.clone());
+                names.insert(name.clone(), sink.as_ref().and_then(Sink::args));
+            }
+        }
+
+        names
+    }
+
+    /// nonce and capability checks of every vuln
+    pub fn checks(&self) -> HashSet<String> {
+        let mut names = HashSet::new();
+        for (_kind, vuln) in self.vulns.iter() {
+            names.extend(vuln.checks.clone());
+        }
+
+        names
+    }
+
+    /// guard functions of every vuln and the arguments they check
+    pub fn guards(&self) -> HashMap<String, Option<Vec<u32>>> {
+        let mut names = HashMap::new();
+        for (_kind, vuln) in self.vulns.iter() {
+            for (name, guard) in vuln.guards.iter() {
+                names.insert(name.clone(), guard.clone());
             }
         }
 
         names
     }
 
-    pub fn hooks(&self) -> &HashSet<String> {
+    pub fn hooks(&self) -> &HashMap<String, Hook> {
         &self.hooks
     }
 
     pub fn vulns(&self) -> &HashMap<String, Vuln> {
         &self.vulns
     }
+
+    pub fn callbacks(&self) -> &HashMap<String, Callback> {
+        &self.callbacks
+    }
+
+    pub fn storage(&self) -> &Storage {
+        &self.storage
+    }
+}
+
+/// a function that registers a callable with wordpress (add_action, add_shortcode)
+/// or runs the callables registered for a tag (do_action, apply_filters).
+/// the hook name is always the first argument
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Hook {
+    // which argument is the callable
+    callable: Option<u32>,
+    // array key holding the callable, register_rest_route('ns', '/r', ['callback' => ...])
+    key: Option<String>,
+    // callable params that are attacker controlled
+    params: Option<Vec<u32>>,
+    // extra settings for hook names starting with these prefixes
+    tags: Option<HashMap<String, HookTag>>,
+    // arguments from this index on are passed to the registered callables
+    dispatch: Option<u32>,
+    // argument naming a hook that cron will run (wp_schedule_event)
+    schedule: Option<u32>,
+    // registered callables are entry points of this kind (shortcode, rest, admin)
+    entry: Option<String>,
+    // who can reach the entry point (nopriv, authenticated, admin)
+    privilege: Option<String>,
+    // array key of a permission callback, missing or __return_true makes it public
+    permission: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct HookTag {
+    // callable params that are attacker controlled for this tag
+    params: Option<Vec<u32>>,
+    // entry point kind and privilege for this tag (wp_ajax_nopriv_)
+    entry: Option<String>,
+    privilege: Option<String>,
+}
+
+impl Hook {
+    pub fn callable(&self) -> Option<usize> {
+        self.callable.map(|c| c as usize)
+    }
+
+    pub fn key(&self) -> Option<&String> {
+        self.key.as_ref()
+    }
+
+    pub fn dispatch(&self) -> Option<usize> {
+        self.dispatch.map(|d| d as usize)
+    }
+
+    pub fn schedule(&self) -> Option<usize> {
+        self.schedule.map(|s| s as usize)
+    }
+
+    pub fn permission(&self) -> Option<&String> {
+        self.permission.as_ref()
+    }
+
+    /// entry point kind and privilege of a registration, the most specific matching tag wins
+    pub fn entry(&self, tag: &str) -> Option<(String, String)> {
+        let mut best: Option<(&String, &HookTag)> = None;
+        for (pattern, settings) in self.tags.iter().flatten() {
+            if Self::tag_matches(pattern, tag)
+                && settings.entry.is_some()
+                && best.is_none_or(|(b, _)| specificity(pattern) > specificity(b))
+            {
+                best = Some((pattern, settings));
+            }
+        }
+
+        let (entry, privilege) = match best {
+            Some((_, settings)) => (settings.entry.clone()?, settings.privilege.clone()),
+            None => (self.entry.clone()?, None),
+        };
+        let privilege = privilege
+            .or(self.privilege.clone())
+            .unwrap_or("unknown".to_string());
+        Some((entry, privilege))
+    }
+
+    /// tainted callable params for a hook name
+    pub fn params(&self, tag: &str) -> Vec<usize> {
+        let mut v: Vec<usize> = Vec::new();
+        if let Some(params) = &self.params {
+            v.extend(params.iter().map(|p| *p as usize));
+        }
+        for (pattern, settings) in self.tags.iter().flatten() {
+            if Self::tag_matches(pattern, tag) {
+                if let Some(params) = &settings.params {
+                    v.extend(params.iter().map(|p| *p as usize));
+                }
+            }
+        }
+        v.sort();
+        v.dedup();
+        v
+    }
+
+    /// hook names in tags are exact or globs, wp_ajax_*
+    fn tag_matches(pattern: &str, tag: &str) -> bool {
+        pattern == tag || (is_glob(pattern) && glob(pattern, tag))
+    }
+}
+
+/// describes how a function like array_map hands data to its callable
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Callback {
+    // which argument is the callable
+    callable: u32,
+    // argument index to the callback params it flows into
+    args: Option<HashMap<u32, Vec<u32>>>,
+    // arguments from this index on are passed positionally (call_user_func)
+    rest: Option<u32>,
+    // array argument spread over every param (call_user_func_array)
+    spread: Option<u32>,
+    // the callback return value is what the function returns
+    returns: Option<bool>,
+}
+
+impl Callback {
+    pub fn callable(&self) -> usize {
+        self.callable as usize
+    }
+
+    /// callback params tainted by the argument at index
+    pub fn params(&self, index: usize, count: usize) -> Vec<usize> {
+        let mut v = Vec::new();
+        if let Some(params) = self.args.as_ref().and_then(|a| a.get(&(index as u32))) {
+            v.extend(params.iter().map(|p| *p as usize));
+        }
+        if let Some(rest) = self.rest {
+            if index >= rest as usize {
+                v.push(index - rest as usize);
+            }
+        }
+        if self.spread == Some(index as u32) {
+            v.extend(0..count);
+        }
+        v
+    }
+
+    pub fn returns(&self) -> bool {
+        self.returns.unwrap_or(false)
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct Waypoint {
     // specify which args sanitize the function
     args: Option<Vec<u32>>,
 }
+
+/// writes and reads of stored data, linked when they use the same store and key
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Storage {
+    #[serde(default)]
+    writes: HashMap<String, Store>,
+    #[serde(default)]
+    reads: HashMap<String, Store>,
+}
+
+impl Storage {
+    /// store a call writes to, matched like sinks so wpdb->insert wants a wpdb receiver
+    pub fn write(&self, call: &CallName, kind: &str) -> Option<(Match, &Store)> {
+        call.best_rule(self.writes.keys(), kind)
+            .map(|(how, rule)| (how, &self.writes[rule]))
+    }
+
+    pub fn read(&self, call: &CallName, kind: &str) -> Option<(Match, &Store)> {
+        call.best_rule(self.reads.keys(), kind)
+            .map(|(how, rule)| (how, &self.reads[rule]))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Store {
+    // option, post_meta, db...
+    store: String,
+    // argument holding the key, any key if missing
+    key: Option<usize>,
+    // argument holding the stored value
+    value: Option<usize>,
+}
+
+impl Store {
+    pub fn store(&self) -> &str {
+        &self.store
+    }
+
+    pub fn key(&self) -> Option<usize> {
+        self.key
+    }
+
+    pub fn value(&self) -> Option<usize> {
+        self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// write rule files into a fresh directory
+    fn files(name: &str, files: &[(&str, &str)]) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("rules-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (file, contents) in files {
+            std::fs::write(dir.join(file), contents).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn includes_are_layered_under_the_including_file() {
+        let dir = files(
+            "layered",
+            &[(
+                "site.yaml",
+                concat!(
+                    "include: [\"default\"]\n",
+                    "vulns:\n",
+                    "  \"xss\":\n",
+                    "    sinks:\n",
+                    "      \"my_echo\":\n",
+                    "    remove: { sinks: [\"print\"] }\n",
+                ),
+            )],
+        );
+        let rules = Rules::from_yaml(dir.join("site.yaml").to_str().unwrap()).unwrap();
+        let xss = &rules.vulns()["xss"];
+        assert!(xss.has_sink(&"my_echo".to_string()));
+        assert!(xss.has_sink(&"echo_statement".to_string()));
+        assert!(!xss.has_sink(&"print".to_string()));
+        assert!(rules.vulns().contains_key("sqli"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn sink_details_override_their_vuln() {
+        let rules = Rules::from_yaml("default").unwrap();
+        let file = rules.vulns()["lfd"].metadata("file");
+        assert_eq!(
+            (file.severity, file.cwe),
+            (Some(Severity::Medium), Some(22))
+        );
+        let eval = rules.vulns()["rce"].metadata("eval");
+        assert_eq!(
+            (eval.severity, eval.cwe),
+            (Some(Severity::Critical), Some(95))
+        );
+        assert_eq!(
+            eval.description.as_deref(),
+            Some("User input is evaluated as PHP code.")
+        );
+        let shell = rules.vulns()["rce"].metadata("shell_command_expression");
+        assert_eq!(shell.cwe, Some(78));
+        assert_eq!(shell.references.len(), 1);
+    }
+
+    #[test]
+    fn including_itself_is_an_error() {
+        let dir = files(
+            "cycle",
+            &[
+                ("a.yaml", "include: [\"b.yaml\"]\n"),
+                ("b.yaml", "include: [\"a.yaml\"]\n"),
+            ],
+        );
+        assert!(Rules::from_yaml(dir.join("a.yaml").to_str().unwrap()).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
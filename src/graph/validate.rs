@@ -0,0 +1,242 @@
+use super::rules::*;
+use crate::tree::output::OutputContext;
+use std::collections::HashSet;
+
+/// a problem in a rule file and the line it is on
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    file: String,
+    line: Option<usize>,
+    message: String,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: warning: {}", self.file, line, self.message),
+            None => write!(f, "{}: warning: {}", self.file, self.message),
+        }
+    }
+}
+
+impl Rules {
+    /// things serde accepts but that can never do what was meant,
+    /// files are the rule files that were merged, included ones first
+    pub fn validate(&self, files: &[(String, String)]) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let mut warn = |(file, line): (String, Option<usize>), message: String| {
+            diagnostics.push(Diagnostic {
+                file,
+                line: line.map(|l| l + 1),
+                message,
+            })
+        };
+
+        for (file, contents) in files {
+            let lines: Vec<&str> = contents.lines().collect();
+            for (line, key) in duplicates(&lines) {
+                warn(
+                    (file.clone(), Some(line)),
+                    format!("duplicate entry {}, only one is used", key),
+                );
+            }
+        }
+
+        let mut names: Vec<&String> = self.vulns().keys().collect();
+        names.sort();
+        for name in names {
+            let vuln = &self.vulns()[name];
+
+            if vuln.sinks().is_empty() || vuln.sources().is_empty() {
+                warn(
+                    locate(files, &["vulns", name]),
+                    format!("vuln {} has no sources or no sinks and never alerts", name),
+                );
+            }
+
+            // names are matched without the $, _GET not $_GET
+            let mut sources: Vec<&String> = vuln.sources().iter().collect();
+            sources.sort();
+            for source in sources.into_iter().filter(|s| s.starts_with('$')) {
+                warn(
+                    locate(files, &["vulns", name, "sources", source]),
+                    format!(
+                        "source {} in {} never matches, use {}",
+                        source,
+                        name,
+                        source.trim_start_matches('$')
+                    ),
+                );
+            }
+
+            let mut sinks: Vec<&String> = vuln.sinks().keys().collect();
+            sinks.sort();
+            for sink in sinks {
+                if vuln.has_sanitizer(sink) || vuln.has_guard(sink) {
+                    warn(
+                        locate(files, &["vulns", name, "sinks", sink]),
+                        format!("{} is both a sink and a sanitizer in {}", sink, name),
+                    );
+                }
+            }
+
+            let mut contexts: Vec<&String> = vuln.contexts().keys().collect();
+            contexts.sort();
+            let known: Vec<String> = OutputContext::ALL.iter().map(|c| c.to_string()).collect();
+            for context in contexts.into_iter().filter(|c| !known.contains(c)) {
+                warn(
+                    locate(files, &["vulns", name, "contexts", context]),
+                    format!(
+                        "context {} in {} is never used, use one of {}",
+                        context,
+                        name,
+                        known.join(", ")
+                    ),
+                );
+            }
+        }
+
+        let mut hooks: Vec<(&String, &Hook)> = self.hooks().iter().collect();
+        hooks.sort_by_key(|(name, _)| *name);
+        for (name, hook) in hooks {
+            if hook.callable().is_none() && hook.dispatch().is_none() && hook.schedule().is_none() {
+                warn(
+                    locate(files, &["hooks", name]),
+                    format!(
+                        "hook {} has no callable, dispatch or schedule and does nothing",
+                        name
+                    ),
+                );
+            }
+        }
+
+        diagnostics
+    }
+}
+
+/// file and line of an entry by its keys, the last file to mention it wins,
+/// or the closest enclosing key when the entry is written inline
+fn locate(files: &[(String, String)], path: &[&str]) -> (String, Option<usize>) {
+    let mut best = (0, files.last().map(|(file, _)| file.clone()), None);
+    for (file, contents) in files.iter().rev() {
+        let lines: Vec<&str> = contents.lines().collect();
+        let mut range = 0..lines.len();
+        for (depth, name) in path.iter().enumerate() {
+            let line = match find(&lines, range.clone(), name) {
+                Some(line) => line,
+                None => break,
+            };
+            if depth + 1 > best.0 {
+                best = (depth + 1, Some(file.clone()), Some(line));
+            }
+            range = line + 1..end(&lines, line);
+        }
+    }
+    (best.1.unwrap_or_default(), best.2)
+}
+
+/// first line in a range with this key or list item
+fn find(lines: &[&str], range: std::ops::Range<usize>, name: &str) -> Option<usize> {
+    range
+        .into_iter()
+        .find(|i| key(lines[*i]).is_some_and(|(_, key)| key == name))
+}
+
+/// end of the block starting at a line, the next line indented as far or less
+fn end(lines: &[&str], start: usize) -> usize {
+    let depth = indent(lines[start]);
+    (start + 1..lines.len())
+        .find(|i| key(lines[*i]).is_some() && indent(lines[*i]) <= depth)
+        .unwrap_or(lines.len())
+}
+
+/// keys and list items repeated in the same mapping or list
+fn duplicates(lines: &[&str]) -> Vec<(usize, String)> {
+    let mut found = Vec::new();
+    // keys seen at each open indentation
+    let mut stack: Vec<(usize, HashSet<String>)> = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let (indent, key) = match key(line) {
+            Some(key) => key,
+            None => continue,
+        };
+        while stack.last().is_some_and(|(open, _)| *open > indent) {
+            stack.pop();
+        }
+        match stack.last_mut() {
+            Some((open, seen)) if *open == indent => {
+                if !seen.insert(key.clone()) {
+                    found.push((i, key));
+                }
+            }
+            _ => stack.push((indent, HashSet::from([key]))),
+        }
+    }
+    found
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// indentation and unquoted key of a mapping line or value of a list item
+fn key(line: &str) -> Option<(usize, String)> {
+    let text = line.trim();
+    if text.is_empty() || text.starts_with('#') || text == "---" {
+        return None;
+    }
+    // list items count as nested one deeper than their dash
+    let (indent, text) = match text.strip_prefix("- ") {
+        Some(item) => (indent(line) + 2, item.trim()),
+        None => (indent(line), text),
+    };
+
+    let key = match text.chars().next()? {
+        quote @ ('"' | '\'') => text[1..].split(quote).next()?,
+        _ => match text.find(": ") {
+            Some(colon) => &text[..colon],
+            None => text.trim_end_matches(':'),
+        },
+    };
+    Some((indent, key.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_of_lines() {
+        assert_eq!(key("xss:"), Some((0, "xss".to_string())));
+        assert_eq!(key("  coercion: false"), Some((2, "coercion".to_string())));
+        assert_eq!(key("    - esc_html*"), Some((6, "esc_html*".to_string())));
+        assert_eq!(
+            key("  - \"wpdb->insert\": x"),
+            Some((4, "wpdb->insert".to_string()))
+        );
+        assert_eq!(key("  'a: b': 1"), Some((2, "a: b".to_string())));
+        assert_eq!(key("  # comment"), None);
+        assert_eq!(key("---"), None);
+        assert_eq!(key(""), None);
+    }
+
+    #[test]
+    fn duplicate_keys_in_the_same_block() {
+        let lines = [
+            "xss:",
+            "  sinks:",
+            "    - echo",
+            "    - print",
+            "    - echo",
+            "  sanitizers:",
+            "    - echo",
+            "sqli:",
+            "  sinks:",
+            "xss:",
+        ];
+        assert_eq!(
+            duplicates(&lines),
+            vec![(4, "echo".to_string()), (9, "xss".to_string())]
+        );
+    }
+}
@@ -1,13 +1,14 @@
 use crate::analyzer::taint::*;
 use crate::tree::cursor::*;
-use std::collections::HashMap;
 
 #[derive(Clone)]
 pub struct Vertex<'a> {
     assign: Option<Taint>,
     context: ContextStack,
-    parents: HashMap<Cursor<'a>, PathItem<'a>>,
-    global_sources: HashMap<Cursor<'a>, PathItem<'a>>,
+    // a parent can reach us along several paths, once for each entry point
+    // a function body runs for
+    parents: Vec<(Cursor<'a>, PathItem<'a>)>,
+    global_sources: Vec<(Cursor<'a>, PathItem<'a>)>,
 }
 
 impl<'a> Vertex<'a> {
@@ -15,25 +16,34 @@ impl<'a> Vertex<'a> {
         Self {
             assign,
             context,
-            parents: HashMap::new(),
-            global_sources: HashMap::new(),
+            parents: Vec::new(),
+            global_sources: Vec::new(),
         }
     }
 
-    pub fn parents(&self) -> &HashMap<Cursor<'a>, PathItem<'a>> {
+    pub fn parents(&self) -> &Vec<(Cursor<'a>, PathItem<'a>)> {
         &self.parents
     }
 
     pub fn add_parent(&mut self, parent: Cursor<'a>, path: PathItem<'a>) {
-        self.parents.insert(parent, path);
+        Self::add_edge(&mut self.parents, parent, path);
     }
 
-    pub fn sources(&self) -> &HashMap<Cursor<'a>, PathItem<'a>> {
+    pub fn sources(&self) -> &Vec<(Cursor<'a>, PathItem<'a>)> {
         &self.global_sources
     }
 
     pub fn add_source(&mut self, source: Cursor<'a>, path: PathItem<'a>) {
-        self.global_sources.insert(source, path);
+        Self::add_edge(&mut self.global_sources, source, path);
+    }
+
+    fn add_edge(edges: &mut Vec<(Cursor<'a>, PathItem<'a>)>, at: Cursor<'a>, path: PathItem<'a>) {
+        if !edges
+            .iter()
+            .any(|(cursor, item)| *cursor == at && *item == path)
+        {
+            edges.push((at, path));
+        }
     }
 
     pub fn context(&self) -> &ContextStack {
@@ -49,11 +59,29 @@ impl<'a> Vertex<'a> {
 pub struct PathItem<'a> {
     source: Taint,
     path: Vec<Cursor<'a>>,
+    // context the step was taken in, None for links the graph adds afterwards
+    context: Option<ContextStack>,
 }
 
 impl<'a> PathItem<'a> {
     pub fn new(source: Taint, path: Vec<Cursor<'a>>) -> Self {
-        Self { source, path }
+        Self {
+            source,
+            path,
+            context: None,
+        }
+    }
+
+    /// the same step, taken in a context
+    pub fn within(self, context: ContextStack) -> Self {
+        Self {
+            context: Some(context),
+            ..self
+        }
+    }
+
+    pub fn context(&self) -> Option<&ContextStack> {
+        self.context.as_ref()
     }
 
     pub fn contains(&self, cursor: &'a Cursor<'a>) -> bool {
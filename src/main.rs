@@ -1,7 +1,9 @@
 use analyzer::analyzer::*;
+use graph::finding::Confidence;
 use graph::rules::*;
 use std::{io, io::prelude::*};
 use tree::file::*;
+use utils::composer::*;
 
 //pub mod repository;
 pub mod analyzer;
@@ -10,7 +12,77 @@ pub mod tree;
 pub mod utils;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let rules = Rules::from_yaml("new.yaml")?;
+    // analyzer check-rules [file], report problems in a rule file and exit
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("check-rules") {
+        let filename = match args.get(2) {
+            Some(filename) => filename.as_str(),
+            None if std::path::Path::new("new.yaml").exists() => "new.yaml",
+            None => "default",
+        };
+        let (_, warnings) = match Rules::check(filename) {
+            Ok(checked) => checked,
+            Err(e) => {
+                println!("{}", e);
+                std::process::exit(1);
+            }
+        };
+        for warning in warnings.iter() {
+            println!("{}", warning);
+        }
+        println!("{}: {} warnings", filename, warnings.len());
+        return Ok(());
+    }
+
+    // analyzer test-rules [file], run the snippets in each vuln against its rules
+    if args.get(1).map(String::as_str) == Some("test-rules") {
+        let rules = match args.get(2) {
+            Some(filename) => Rules::from_yaml(filename)?,
+            None => Rules::load("new.yaml")?,
+        };
+        let (count, failures) = analyzer::ruletest::run(&rules);
+        for failure in failures.iter() {
+            print!("{}", failure);
+        }
+        println!(
+            "{} passed, {} failed",
+            count - failures.len(),
+            failures.len()
+        );
+        if !failures.is_empty() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // analyzer test-fixtures [paths], compare findings to the expect annotations in php fixtures
+    if args.get(1).map(String::as_str) == Some("test-fixtures") {
+        let rules = Rules::load("new.yaml")?;
+        let paths = match args.len() > 2 {
+            true => args[2..].to_vec(),
+            false => vec!["fixtures".to_string()],
+        };
+        let (count, mismatches) = analyzer::fixtures::run(&rules, &paths)?;
+        for mismatch in mismatches.iter() {
+            println!("{}", mismatch);
+        }
+        println!("{} fixtures, {} mismatches", count, mismatches.len());
+        if !mismatches.is_empty() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // analyzer --min-confidence medium, leave out findings we are less sure about
+    let min_confidence: Confidence = match args.iter().position(|arg| arg == "--min-confidence") {
+        Some(i) => args.get(i + 1).map_or("low", String::as_str).parse()?,
+        None => Confidence::Low,
+    };
+
+    // analyzer --skip-errors, dont analyze code that failed to parse
+    let skip_errors = args.iter().any(|arg| arg == "--skip-errors");
+
+    let rules = Rules::load("new.yaml")?;
     for line in io::stdin().lock().lines() {
         let mut files = Vec::new();
         for word in line.unwrap().split(' ') {
@@ -22,6 +94,19 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 if let Ok(file) = File::from_url(word) {
                     files.push(file);
                 }
+            } else if word.ends_with("composer.json") {
+                // load everything the package autoloads
+                eprintln!("reading autoload {}", word);
+                match Composer::new(word) {
+                    Ok(composer) => {
+                        for name in composer.files() {
+                            if let Ok(file) = File::new(&name) {
+                                files.push(file);
+                            }
+                        }
+                    }
+                    Err(e) => eprintln!("bad composer.json {}: {}", word, e),
+                }
             } else {
                 eprintln!("reading {}", word);
                 if let Ok(file) = File::new(word) {
@@ -30,30 +115,94 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
 
-        let file_refs: Vec<&File> = files.iter().map(|file| -> &File { &file }).collect();
+        let file_refs: Vec<&File> = files.iter().map(|file| -> &File { file }).collect();
+
+        // broken files are still analyzed, report where so findings there can be judged
+        let mut parse_errors = 0;
+        for file in files.iter() {
+            for error in file.parse_errors() {
+                eprintln!("{}", error);
+                parse_errors += 1;
+            }
+        }
 
         let dumper = crate::utils::dumper::Dumper::new(file_refs.clone());
         eprintln!("{}", dumper.dump());
 
         // create analyzer
         let mut analyzer = Analyzer::new(file_refs, &rules);
+        if skip_errors {
+            analyzer.skip_errors();
+        }
         // get populated flow graph
         eprintln!("analyzing tree");
         let graph = analyzer.graph();
         eprintln!("{}", graph.dump());
 
-        let paths = graph.match_rules(&rules);
+        // most severe first for triage
+        let mut findings: Vec<_> = graph
+            .match_rules(&rules)
+            .into_iter()
+            .filter(|finding| finding.confidence() >= min_confidence)
+            .collect();
+        findings.sort_by_key(|finding| std::cmp::Reverse(finding.metadata().severity));
         println!("---");
-        for path in paths.iter() {
-            let filename = path.first().unwrap().filename();
-            println!("file: {}", filename);
-            println!("type: 'sqli'");
+        for finding in findings.iter() {
+            let metadata = finding.metadata();
+            println!("file: {}", finding.filename());
+            println!("type: '{}'", finding.vuln());
+            if let Some(severity) = metadata.severity {
+                println!("severity: '{}'", severity);
+            }
+            if let Some(cwe) = metadata.cwe {
+                println!("cwe: 'CWE-{}'", cwe);
+            }
+            println!("confidence: '{}'", finding.confidence());
+            println!("score: {}", finding.score());
+            if !finding.approximations().is_empty() {
+                println!("approximations:");
+                for approximation in finding.approximations().iter() {
+                    println!("  - {}", approximation);
+                }
+            }
+            if let Some(entry) = finding.entry() {
+                println!("entry: '{} {}'", entry.kind, entry.name);
+                println!("privilege: '{}'", entry.privilege);
+            }
+            if let Some(context) = finding.context() {
+                println!("context: '{}'", context);
+            }
+            if let Some(escaper) = finding.mismatch() {
+                println!(
+                    "mismatch: '{} does not escape for {} output'",
+                    escaper,
+                    finding.context().map_or(String::new(), |c| c.to_string())
+                );
+            }
+            if let Some(description) = &metadata.description {
+                println!("description: '{}'", description.replace('\'', "''"));
+            }
+            if let Some(remediation) = &metadata.remediation {
+                println!("remediation: '{}'", remediation.replace('\'', "''"));
+            }
+            if !metadata.references.is_empty() {
+                println!("references:");
+                for reference in metadata.references.iter() {
+                    println!("  - {}", reference);
+                }
+            }
             println!("path:");
-            for vert in path.iter() {
-                println!("  - {}", vert.to_string());
+            for vert in finding.path().iter() {
+                println!("  - {}", vert);
             }
             println!("---");
         }
+        eprintln!(
+            "{} files, {} parse errors, {} findings",
+            files.len(),
+            parse_errors,
+            findings.len()
+        );
     }
     Ok(())
 }
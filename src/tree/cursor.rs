@@ -36,6 +36,13 @@ impl<'a> std::fmt::Debug for Cursor<'a> {
     }
 }
 
+// the source code of the current node
+impl<'a> std::fmt::Display for Cursor<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_str())
+    }
+}
+
 impl<'a> Cursor<'a> {
     pub fn new(cursor: TreeCursor<'a>, file: &'a File) -> Self {
         Self { cursor, file }
@@ -48,6 +55,10 @@ impl<'a> Cursor<'a> {
         }
     }
 
+    pub fn file(&self) -> &'a File {
+        self.file
+    }
+
     pub fn filename(&self) -> String {
         self.file.name()
     }
@@ -60,15 +71,23 @@ impl<'a> Cursor<'a> {
         self.cursor.field_name()
     }
 
-    pub fn traverse(&self) -> Traversal {
-        Traversal::new(&self)
+    pub fn traverse(&self) -> Traversal<'_> {
+        Traversal::new(self)
     }
 
-    pub fn traverse_block(&self) -> Traversal {
-        Traversal::new_block(&self, vec!["method_declaration", "function_definition"])
+    pub fn traverse_block(&self) -> Traversal<'a> {
+        Traversal::new_block(
+            self,
+            vec![
+                "method_declaration",
+                "function_definition",
+                "anonymous_function_creation_expression",
+                "arrow_function",
+            ],
+        )
     }
 
-    pub fn trace(&self) -> Trace {
+    pub fn trace(&self) -> Trace<'_> {
         Trace::new(self.clone())
     }
 
@@ -164,11 +183,63 @@ impl<'a> Cursor<'a> {
         index
     }
 
-    /// get the source code of the current node
-    pub fn to_string(&self) -> String {
+    /// contents of a string literal without the quotes
+    pub fn string_value(&self) -> Option<String> {
+        match self.kind() {
+            "string" => {
+                let s = self.to_str();
+                Some(s.trim_matches(|c| c == '\'' || c == '"').to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// expression printed by a short echo tag, <?= $x ?>
+    pub fn is_short_echo(&self) -> bool {
         let node = self.cursor.node();
-        let slice = &self.file.get_source()[node.byte_range()];
-        slice.to_string()
+        // the tag ends the html before it when there is some, ?><p><?= $x ?>
+        let tag = match node.prev_sibling() {
+            Some(html) if html.kind() == "text_interpolation" => {
+                html.child(html.child_count().saturating_sub(1))
+            }
+            tag => tag,
+        };
+        match tag {
+            Some(tag) if node.kind() == "expression_statement" && tag.kind() == "php_tag" => {
+                &self.file.get_source()[tag.byte_range()] == "<?="
+            }
+            _ => false,
+        }
+    }
+
+    /// html written out of php tags before this node, in file order
+    pub fn html_before(&self) -> String {
+        let end = self.cursor.node().start_byte();
+        let mut html = String::new();
+        let mut cursor = self.file.raw_cursor();
+        loop {
+            let node = cursor.node();
+            if node.kind() == "text" && node.end_byte() <= end {
+                html.push_str(&self.file.get_source()[node.byte_range()]);
+            }
+            // nothing past the node can come before it
+            if node.start_byte() < end && cursor.goto_first_child() {
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return html;
+                }
+            }
+            if cursor.node().start_byte() >= end {
+                return html;
+            }
+        }
+    }
+
+    /// line the node starts on, counting from 1
+    pub fn row(&self) -> usize {
+        self.cursor.node().start_position().row + 1
     }
 
     /// get the source code of the current node
@@ -13,10 +13,7 @@ pub struct File {
 impl File {
     pub fn new(name: &str) -> Result<Self, Box<dyn Error>> {
         let source = std::fs::read_to_string(name)?;
-        let mut parser = Parser::new();
-        parser.set_language(tree_sitter_php::language())?;
-        let tree = parser.parse(&source, None).unwrap();
-        return Ok(File::from_tree(name, tree, source));
+        File::from_source(name, source)
     }
 
     pub fn from_url(url: &str) -> Result<Self, Box<dyn Error>> {
@@ -25,10 +22,17 @@ impl File {
             .timeout(Duration::from_secs(5))
             .build()?;
         let source = client.get(url).send()?.text()?;
+        File::from_source(url, source)
+    }
+
+    /// parse php held in memory, like snippets in the rules
+    pub fn from_source(name: &str, source: String) -> Result<Self, Box<dyn Error>> {
         let mut parser = Parser::new();
         parser.set_language(tree_sitter_php::language())?;
-        let tree = parser.parse(&source, None).unwrap();
-        Ok(File::from_tree(url, tree, source))
+        let tree = parser
+            .parse(&source, None)
+            .ok_or_else(|| format!("{}: parser gave up", name))?;
+        Ok(File::from_tree(name, tree, source))
     }
 
     pub fn from_tree(name: &str, tree: Tree, source: String) -> Self {
@@ -47,15 +51,99 @@ impl File {
         &self.source
     }
 
-    pub fn raw_cursor(&self) -> TreeCursor {
+    pub fn raw_cursor(&self) -> TreeCursor<'_> {
         self.tree.walk()
     }
 
-    pub fn cursor(&self) -> Cursor {
+    pub fn cursor(&self) -> Cursor<'_> {
         Cursor::new(self.raw_cursor(), self)
     }
 
-    pub fn traverse(&self) -> Traversal {
+    pub fn traverse(&self) -> Traversal<'_> {
         Traversal::from_file(self)
     }
+
+    /// syntax errors and missing tokens tree-sitter recovered from
+    pub fn parse_errors(&self) -> Vec<ParseError> {
+        let mut errors = Vec::new();
+        self.collect_errors(self.tree.root_node(), &mut errors);
+        errors
+    }
+
+    fn collect_errors(&self, node: Node, errors: &mut Vec<ParseError>) {
+        let at = node.start_position();
+        let mut error = ParseError {
+            file: self.name.clone(),
+            line: at.row + 1,
+            column: at.column + 1,
+            missing: None,
+        };
+        // everything under an error node is garbage, one report is enough
+        if node.is_error() {
+            errors.push(error);
+            return;
+        }
+        if node.is_missing() {
+            error.missing = Some(node.kind().to_string());
+            errors.push(error);
+        }
+        if !node.has_error() {
+            return;
+        }
+        for i in 0..node.child_count() {
+            if let Some(child) = node.child(i) {
+                self.collect_errors(child, errors);
+            }
+        }
+    }
+}
+
+/// where a file failed to parse
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    // token the parser inserted to recover, None for a syntax error
+    pub missing: Option<String>,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.missing {
+            Some(kind) => write!(
+                f,
+                "{}:{}:{}: missing {}",
+                self.file, self.line, self.column, kind
+            ),
+            None => write!(
+                f,
+                "{}:{}:{}: syntax error",
+                self.file, self.line, self.column
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(source: &str) -> Vec<String> {
+        let file = File::from_source("t.php", source.to_string()).unwrap();
+        file.parse_errors().iter().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn clean_files_have_no_parse_errors() {
+        assert!(errors("<?php\necho $a;\n").is_empty());
+    }
+
+    #[test]
+    fn parse_errors_point_at_the_broken_code() {
+        assert_eq!(
+            errors("<?php\necho $a;\n$b = ;\necho $b;\n"),
+            ["t.php:3:4: syntax error"]
+        );
+    }
 }
@@ -1,5 +1,8 @@
 pub mod cursor;
 pub mod file;
+pub mod namespace;
+pub mod output;
+pub mod receiver;
 pub mod resolved;
 pub mod tracer;
 pub mod traverser;
@@ -0,0 +1,226 @@
+use super::cursor::*;
+use super::file::*;
+use std::collections::HashMap;
+
+/// the namespace and use imports in effect for part of a file
+#[derive(Clone, Debug)]
+struct Region {
+    start: usize,
+    end: usize,
+    name: String,
+    classes: HashMap<String, String>,
+    functions: HashMap<String, String>,
+}
+
+/// namespace regions of a file, for turning names into fully qualified ones
+#[derive(Clone, Debug)]
+pub struct Namespaces {
+    regions: Vec<Region>,
+}
+
+impl Namespaces {
+    pub fn new(file: &File) -> Self {
+        let mut regions: Vec<Region> = Vec::new();
+        let mut cursor = file.cursor();
+        let end = cursor.raw_cursor().node().end_byte();
+
+        // everything is in the global namespace until declared otherwise
+        regions.push(Region::new(0, end, String::new()));
+
+        if cursor.goto_first_child() {
+            loop {
+                match cursor.kind() {
+                    "namespace_definition" => {
+                        let start = cursor.raw_cursor().node().start_byte();
+                        let mut name = cursor.clone();
+                        let name = match name.goto_field("name") {
+                            true => name.to_string(),
+                            false => String::new(),
+                        };
+
+                        // close the previous statement style namespace
+                        if let Some(last) = regions.last_mut() {
+                            if last.end > start {
+                                last.end = start;
+                            }
+                        }
+
+                        let mut body = cursor.clone();
+                        if body.goto_field("body") {
+                            let range = body.raw_cursor().node().byte_range();
+                            let mut region = Region::new(range.start, range.end, name);
+                            if body.goto_first_child() {
+                                loop {
+                                    if body.kind() == "namespace_use_declaration" {
+                                        region.import(body.clone());
+                                    }
+                                    if !body.goto_next_sibling() {
+                                        break;
+                                    }
+                                }
+                            }
+                            regions.push(region);
+                            // code after a braced namespace is global again
+                            regions.push(Region::new(range.end, end, String::new()));
+                        } else {
+                            regions.push(Region::new(start, end, name));
+                        }
+                    }
+                    "namespace_use_declaration" => {
+                        if let Some(last) = regions.last_mut() {
+                            last.import(cursor.clone());
+                        }
+                    }
+                    _ => (),
+                }
+                if !cursor.goto_next_sibling() {
+                    break;
+                }
+            }
+        }
+
+        Self { regions }
+    }
+
+    fn region(&self, cursor: &Cursor) -> Option<&Region> {
+        let byte = cursor.raw_cursor().node().start_byte();
+        self.regions
+            .iter()
+            .rev()
+            .find(|r| r.start <= byte && byte < r.end)
+    }
+
+    /// namespace the cursor is in, empty for global
+    pub fn namespace(&self, cursor: &Cursor) -> String {
+        match self.region(cursor) {
+            Some(region) => region.name.clone(),
+            None => String::new(),
+        }
+    }
+
+    /// fully qualified name of a class name used at cursor
+    pub fn qualify_class(&self, cursor: &Cursor, name: &str) -> String {
+        match self.region(cursor) {
+            Some(region) => region.qualify(name, &region.classes),
+            None => name.trim_start_matches('\\').to_string(),
+        }
+    }
+
+    /// fully qualified name of a function name used at cursor
+    pub fn qualify_function(&self, cursor: &Cursor, name: &str) -> String {
+        match self.region(cursor) {
+            Some(region) if !name.contains('\\') => region.qualify(name, &region.functions),
+            Some(region) => region.qualify(name, &region.classes),
+            None => name.trim_start_matches('\\').to_string(),
+        }
+    }
+}
+
+impl Region {
+    fn new(start: usize, end: usize, name: String) -> Self {
+        Self {
+            start,
+            end,
+            name,
+            classes: HashMap::new(),
+            functions: HashMap::new(),
+        }
+    }
+
+    /// record the aliases of a use declaration
+    fn import(&mut self, cursor: Cursor) {
+        let mut decl = cursor.clone();
+        let functions = decl.goto_field("type") && decl.to_str() == "function";
+
+        // group uses share a prefix, use A\\{B, C}
+        let mut prefix = String::new();
+        let mut cur = cursor;
+        if !cur.goto_first_child() {
+            return;
+        }
+        loop {
+            match cur.kind() {
+                "namespace_name" => prefix = format!("{}\\", cur.to_str()),
+                "namespace_use_clause" => self.import_clause(cur.clone(), "", functions),
+                "namespace_use_group" => {
+                    let mut clause = cur.clone();
+                    if clause.goto_first_child() {
+                        loop {
+                            if clause.kind() == "namespace_use_clause"
+                                || clause.kind() == "namespace_use_group_clause"
+                            {
+                                self.import_clause(clause.clone(), &prefix, functions);
+                            }
+                            if !clause.goto_next_sibling() {
+                                break;
+                            }
+                        }
+                    }
+                }
+                _ => (),
+            }
+            if !cur.goto_next_sibling() {
+                break;
+            }
+        }
+    }
+
+    fn import_clause(&mut self, cursor: Cursor, prefix: &str, functions: bool) {
+        let mut functions = functions;
+        let mut target = None;
+        let mut alias = None;
+        let mut cur = cursor;
+        if !cur.goto_first_child() {
+            return;
+        }
+        loop {
+            match cur.kind() {
+                "function" => functions = true,
+                "qualified_name" | "namespace_name" | "name" if target.is_none() => {
+                    target = Some(cur.to_string())
+                }
+                "name" => alias = Some(cur.to_string()),
+                _ => (),
+            }
+            if !cur.goto_next_sibling() {
+                break;
+            }
+        }
+
+        if let Some(target) = target {
+            let target = format!("{}{}", prefix, target.trim_start_matches('\\'));
+            let alias = alias.unwrap_or(target.rsplit('\\').next().unwrap_or_default().to_string());
+            match functions {
+                true => self.functions.insert(alias, target),
+                false => self.classes.insert(alias, target),
+            };
+        }
+    }
+
+    /// resolve a name against imports, or prefix it with the namespace
+    fn qualify(&self, name: &str, imports: &HashMap<String, String>) -> String {
+        if let Some(name) = name.strip_prefix('\\') {
+            return name.to_string();
+        }
+        if let Some(name) = name.strip_prefix("namespace\\") {
+            return self.prefixed(name);
+        }
+
+        let (first, rest) = match name.split_once('\\') {
+            Some((first, rest)) => (first, Some(rest)),
+            None => (name, None),
+        };
+        match (imports.get(first), rest) {
+            (Some(target), Some(rest)) => format!("{}\\{}", target, rest),
+            (Some(target), None) => target.clone(),
+            (None, _) => self.prefixed(name),
+        }
+    }
+
+    fn prefixed(&self, name: &str) -> String {
+        match self.name.is_empty() {
+            true => name.to_string(),
+            false => format!("{}\\{}", self.name, name),
+        }
+    }
+}
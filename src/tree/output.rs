@@ -0,0 +1,105 @@
+use super::cursor::Cursor;
+
+// attributes whose value is loaded as a url
+const URL_ATTRIBUTES: [&str; 8] = [
+    "href",
+    "src",
+    "action",
+    "formaction",
+    "poster",
+    "cite",
+    "background",
+    "data",
+];
+
+/// where in a page an echo writes, decides which escaper makes it safe
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum OutputContext {
+    Html,
+    Attribute,
+    Url,
+    Script,
+}
+
+impl std::fmt::Display for OutputContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputContext::Html => write!(f, "html"),
+            OutputContext::Attribute => write!(f, "attribute"),
+            OutputContext::Url => write!(f, "url"),
+            OutputContext::Script => write!(f, "script"),
+        }
+    }
+}
+
+impl OutputContext {
+    pub const ALL: [OutputContext; 4] = [
+        OutputContext::Html,
+        OutputContext::Attribute,
+        OutputContext::Url,
+        OutputContext::Script,
+    ];
+
+    /// context of an echo from the html around it, the body when there is none
+    pub fn of(cursor: &Cursor) -> Self {
+        Self::after(&cursor.html_before())
+    }
+
+    /// context at the end of some html
+    pub fn after(html: &str) -> Self {
+        let html = html.to_lowercase();
+
+        // inside a script element, <script>var x = "<?= $x ?>"
+        if html.rfind("<script") > html.rfind("</script") {
+            return OutputContext::Script;
+        }
+
+        // inside a tag when the last < is not closed, <a href="<?= $x ?>"
+        let open = match html.rfind('<') {
+            Some(open) if html.rfind('>').is_none_or(|close| close < open) => open,
+            _ => return OutputContext::Html,
+        };
+        let tag = &html[open..];
+        let name = match tag.rfind('=') {
+            Some(eq) => tag[..eq]
+                .trim_end()
+                .rsplit(|c: char| c.is_whitespace())
+                .next()
+                .unwrap_or_default(),
+            None => return OutputContext::Attribute,
+        };
+        match name {
+            name if URL_ATTRIBUTES.contains(&name) => OutputContext::Url,
+            // event handlers run their value, onclick="<?= $x ?>"
+            name if name.starts_with("on") => OutputContext::Script,
+            _ => OutputContext::Attribute,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn context_after_html() {
+        assert_eq!(OutputContext::after(""), OutputContext::Html);
+        assert_eq!(OutputContext::after("<p>"), OutputContext::Html);
+        assert_eq!(
+            OutputContext::after("<p class=\""),
+            OutputContext::Attribute
+        );
+        assert_eq!(OutputContext::after("<input "), OutputContext::Attribute);
+        assert_eq!(OutputContext::after("<a HREF=\""), OutputContext::Url);
+        assert_eq!(OutputContext::after("<img src = '"), OutputContext::Url);
+        assert_eq!(OutputContext::after("<b onclick=\""), OutputContext::Script);
+        assert_eq!(
+            OutputContext::after("<script>var x = \""),
+            OutputContext::Script
+        );
+        assert_eq!(
+            OutputContext::after("<script></script><p>"),
+            OutputContext::Html
+        );
+    }
+}
@@ -0,0 +1,243 @@
+use super::cursor::*;
+use super::namespace::*;
+use super::traverser::*;
+use crate::utils::glob::*;
+
+/// how a rule name matched, a rule for some receiver matches unknown receivers by name only,
+/// exact names take precedence over patterns
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Match {
+    NameOnly,
+    Pattern,
+    Exact,
+}
+
+/// a call as rules see it, a bare name and the receiver class if we know it
+#[derive(Clone, Debug)]
+pub struct CallName {
+    pub name: String,
+    // "->" for methods, "::" for static calls, empty for functions
+    pub op: &'static str,
+    pub receiver: Option<String>,
+}
+
+impl CallName {
+    pub fn new(cursor: &Cursor, name: String) -> Self {
+        let mut call = Self {
+            name,
+            op: "",
+            receiver: None,
+        };
+
+        match cursor.kind() {
+            "member_call_expression" | "nullsafe_member_call_expression" => {
+                call.op = "->";
+                let mut object = cursor.clone();
+                if object.goto_field("object") {
+                    call.receiver = receiver_class(object);
+                }
+            }
+            "scoped_call_expression" => {
+                call.op = "::";
+                let mut scope = cursor.clone();
+                if scope.goto_field("scope") {
+                    call.receiver = match scope.to_str() {
+                        "self" | "static" => enclosing_class(cursor.clone()),
+                        "parent" => None,
+                        class => Some(qualify(&scope, class)),
+                    };
+                }
+            }
+            _ => (),
+        }
+
+        call
+    }
+
+    /// rule name for this call with its receiver, wpdb->query
+    pub fn qualified(&self) -> Option<String> {
+        let receiver = self.receiver.as_ref()?;
+        Some(format!("{}{}{}", receiver, self.op, self.name))
+    }
+
+    /// match a rule name, bare names match any receiver like they always have
+    pub fn matches(&self, rule: &str, kind: &str) -> Option<Match> {
+        if rule == kind || rule == self.name {
+            return Some(Match::Exact);
+        }
+        let qualified = self.qualified();
+        if let Some(qualified) = &qualified {
+            if rule.eq_ignore_ascii_case(qualified) {
+                return Some(Match::Exact);
+            }
+        }
+
+        // esc_*, mysql*_query or wpdb->get_*
+        if is_glob(rule) {
+            let receiver = qualified
+                .as_ref()
+                .is_some_and(|qualified| glob(&rule.to_lowercase(), &qualified.to_lowercase()));
+            if receiver || glob(rule, kind) || glob(rule, &self.name) {
+                return Some(Match::Pattern);
+            }
+        }
+
+        match qualified {
+            // Foo->bar or Foo::bar for a receiver we couldnt type
+            None if self.is_method_of(rule) => Some(Match::NameOnly),
+            _ => None,
+        }
+    }
+
+    /// best match among a set of rule names
+    pub fn best<'b>(&self, rules: impl Iterator<Item = &'b String>, kind: &str) -> Option<Match> {
+        self.best_rule(rules, kind).map(|(how, _)| how)
+    }
+
+    /// best matching rule name, the most specific pattern wins between patterns
+    pub fn best_rule<'b>(
+        &self,
+        rules: impl Iterator<Item = &'b String>,
+        kind: &str,
+    ) -> Option<(Match, &'b String)> {
+        rules
+            .filter_map(|rule| self.matches(rule, kind).map(|how| (how, rule)))
+            .max_by_key(|(how, rule)| (*how, specificity(rule)))
+    }
+
+    fn is_method_of(&self, rule: &str) -> bool {
+        !self.op.is_empty()
+            && rule
+                .split_once(self.op)
+                .is_some_and(|(_, method)| glob(&method.to_lowercase(), &self.name.to_lowercase()))
+    }
+}
+
+/// class of the object a method is called on, when declared nearby
+pub fn receiver_class(object: Cursor) -> Option<String> {
+    match object.kind() {
+        "object_creation_expression" => created_class(object),
+        "parenthesized_expression" => {
+            let mut inner = object;
+            inner.goto_child(1);
+            receiver_class(inner)
+        }
+        "variable_name" => {
+            let name = object.name()?;
+            if name == "this" {
+                return enclosing_class(object);
+            }
+            variable_class(object, &name)
+        }
+        _ => None,
+    }
+}
+
+/// look through the enclosing function for what a variable holds
+fn variable_class(cursor: Cursor, name: &str) -> Option<String> {
+    let before = cursor.raw_cursor().node().start_byte();
+    let mut scope = cursor;
+    while scope.goto_parent() {
+        match scope.kind() {
+            "function_definition"
+            | "method_declaration"
+            | "anonymous_function_creation_expression"
+            | "arrow_function"
+            | "program" => break,
+            _ => (),
+        }
+    }
+
+    // typed params, function f(WP_REST_Request $request)
+    let mut class = None;
+    let mut params = scope.clone();
+    if params.goto_field("parameters") {
+        for motion in params.traverse() {
+            if let Order::Enter(param) = motion {
+                let mut var = param.clone();
+                let mut type_node = param;
+                if var.goto_field("name")
+                    && var.name().as_deref() == Some(name)
+                    && type_node.goto_field("type")
+                {
+                    let hint = type_node.to_str().trim_start_matches('?');
+                    class = Some(qualify(&type_node, hint));
+                }
+            }
+        }
+    }
+
+    // the last global or new before the call wins
+    let mut body = scope.clone();
+    if body.kind() != "program" && !body.goto_field("body") {
+        return class;
+    }
+    for motion in body.traverse_block() {
+        if let Order::Enter(cur) = motion {
+            if cur.raw_cursor().node().start_byte() >= before {
+                break;
+            }
+            match cur.kind() {
+                // wordpress globals are named after their class, global $wpdb
+                "global_declaration" => {
+                    for motion in cur.traverse() {
+                        if let Order::Enter(var) = motion {
+                            if var.kind() == "variable_name" && var.name().as_deref() == Some(name)
+                            {
+                                class = Some(name.to_string());
+                            }
+                        }
+                    }
+                }
+                "assignment_expression" => {
+                    let (mut left, mut right) = (cur.clone(), cur.clone());
+                    if left.goto_field("left")
+                        && left.kind() == "variable_name"
+                        && left.name().as_deref() == Some(name)
+                        && right.goto_field("right")
+                    {
+                        class = created_class(right);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    class
+}
+
+/// class instantiated by new Foo()
+fn created_class(cursor: Cursor) -> Option<String> {
+    if cursor.kind() != "object_creation_expression" {
+        return None;
+    }
+    let mut class = cursor;
+    class.goto_first_child();
+    while class.goto_next_sibling() {
+        if class.kind() == "name" || class.kind() == "qualified_name" {
+            return Some(qualify(&class, class.to_str()));
+        }
+    }
+    None
+}
+
+/// class name as written at cursor made fully qualified, \WP_REST_Request or an imported alias
+fn qualify(cursor: &Cursor, name: &str) -> String {
+    Namespaces::new(cursor.file()).qualify_class(cursor, name)
+}
+
+/// name of the class a cursor is inside of
+pub fn enclosing_class(cursor: Cursor) -> Option<String> {
+    let mut cur = cursor;
+    while cur.goto_parent() {
+        if cur.kind() == "class_declaration" || cur.kind() == "trait_declaration" {
+            let mut name = cur.clone();
+            return match name.goto_field("name") {
+                true => Some(name.to_string()),
+                false => None,
+            };
+        }
+    }
+    None
+}
@@ -4,6 +4,7 @@ use super::{cursor::*, traverser::Order};
 #[derive(Clone, Hash, Eq, PartialEq)]
 pub enum Resolved<'a> {
     Function { cursor: Cursor<'a> },
+    Closure { cursor: Cursor<'a> },
     Root { cursor: Cursor<'a> },
 }
 
@@ -16,9 +17,21 @@ impl<'a> Resolved<'a> {
         Self::Root { cursor }
     }
 
+    /// anonymous functions and arrow functions
+    pub fn new_closure(cursor: Cursor<'a>) -> Self {
+        Self::Closure { cursor }
+    }
+
+    /// closures have no name, so identify them by position
+    pub fn closure_name(cursor: &Cursor) -> String {
+        let pos = cursor.raw_cursor().node().start_position();
+        format!("{{closure}}:{}:{}", pos.row + 1, pos.column)
+    }
+
     pub fn cursor(&self) -> Cursor<'a> {
         match self {
             Resolved::Function { cursor } => cursor.clone(),
+            Resolved::Closure { cursor } => cursor.clone(),
             Resolved::Root { cursor } => cursor.clone(),
         }
     }
@@ -29,13 +42,13 @@ impl<'a> Resolved<'a> {
         let mut v = Vec::new();
 
         match self {
-            Resolved::Function { cursor } => {
+            Resolved::Function { cursor } | Resolved::Closure { cursor } => {
                 let mut cursor = cursor.clone();
                 if !cursor.goto_field("parameters") {
                     return v;
                 }
-                let mut traversal = Traversal::new(&cursor);
-                while let Some(motion) = traversal.next() {
+                let traversal = Traversal::new(&cursor);
+                for motion in traversal {
                     if let Order::Enter(cur) = motion {
                         if cur.kind() == "variable_name" {
                             v.push(cur.clone());
@@ -61,6 +74,9 @@ impl<'a> Resolved<'a> {
     }
 
     pub fn name(&self) -> String {
-        self.cursor().name().unwrap()
+        match self {
+            Resolved::Closure { cursor } => Self::closure_name(cursor),
+            _ => self.cursor().name().unwrap(),
+        }
     }
 }
@@ -20,12 +20,12 @@ impl<'s> Iterator for Trace<'s> {
     fn next(&mut self) -> Option<Self::Item> {
         if self.cursor.goto_parent() {
             if self.concrete || self.cursor.raw_cursor().node().is_named() {
-                return Some(self.cursor.clone());
+                Some(self.cursor.clone())
             } else {
-                return self.next();
+                self.next()
             }
         } else {
-            return None;
+            None
         }
     }
 }
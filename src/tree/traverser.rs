@@ -96,22 +96,22 @@ impl<'a> Traversal<'a> {
             if self.cursor.goto_next_sibling() {
                 // we havent visited this yet, break out of leave loop
                 self.visited = false;
-                return Some(Order::Leave(last));
+                Some(Order::Leave(last))
             } else if self.cursor.goto_parent() {
-                return Some(Order::Leave(last));
+                Some(Order::Leave(last))
             } else {
                 // break if we are at the root node
                 self.end = true;
-                return Some(Order::Leave(last));
+                Some(Order::Leave(last))
             }
         } else {
             // if not visited, keep entering child nodes
             if self.cursor.goto_first_child() {
-                return Some(Order::Enter(last));
+                Some(Order::Enter(last))
             } else {
                 // we are at a leaf, turn around
                 self.visited = true;
-                return Some(Order::Enter(self.cursor.clone()));
+                Some(Order::Enter(self.cursor.clone()))
             }
         }
     }
@@ -0,0 +1,103 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+/// autoload section of a composer.json
+#[derive(Debug, Default, Deserialize)]
+pub struct Autoload {
+    #[serde(default, rename = "psr-4")]
+    psr4: HashMap<String, Paths>,
+    #[serde(default, rename = "psr-0")]
+    psr0: HashMap<String, Paths>,
+    #[serde(default)]
+    classmap: Vec<String>,
+    #[serde(default)]
+    files: Vec<String>,
+}
+
+/// autoload maps take a single path or a list of them
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Paths {
+    One(String),
+    Many(Vec<String>),
+}
+
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    autoload: Autoload,
+}
+
+/// a composer package, used to find the php files it autoloads
+#[derive(Debug)]
+pub struct Composer {
+    root: PathBuf,
+    autoload: Autoload,
+}
+
+impl Composer {
+    pub fn new(name: &str) -> Result<Self, Box<dyn Error>> {
+        let source = std::fs::read_to_string(name)?;
+        // json is yaml, so we dont need another parser
+        let manifest: Manifest = serde_yaml::from_str(&source)?;
+        let root = Path::new(name)
+            .parent()
+            .unwrap_or(Path::new(""))
+            .to_path_buf();
+
+        Ok(Self {
+            root,
+            autoload: manifest.autoload,
+        })
+    }
+
+    /// every php file the package autoloads
+    pub fn files(&self) -> Vec<String> {
+        let mut files = Vec::new();
+        let dirs = self
+            .autoload
+            .psr4
+            .values()
+            .chain(self.autoload.psr0.values())
+            .flat_map(|paths| paths.to_vec());
+
+        for dir in dirs.chain(self.autoload.classmap.iter().cloned()) {
+            Self::php_files(&self.root.join(dir), &mut files);
+        }
+        for file in self.autoload.files.iter() {
+            Self::php_files(&self.root.join(file), &mut files);
+        }
+
+        files.sort();
+        files.dedup();
+        files
+    }
+
+    /// collect php files under a path, skipping installed dependencies
+    fn php_files(path: &Path, files: &mut Vec<String>) {
+        if path.is_file() {
+            if path.extension().is_some_and(|ext| ext == "php") {
+                files.push(path.to_string_lossy().to_string());
+            }
+        } else if let Ok(entries) = std::fs::read_dir(path) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.file_name().is_some_and(|name| name == "vendor") {
+                    continue;
+                }
+                Self::php_files(&path, files);
+            }
+        }
+    }
+}
+
+impl Paths {
+    fn to_vec(&self) -> Vec<String> {
+        match self {
+            Paths::One(path) => vec![path.clone()],
+            Paths::Many(paths) => paths.clone(),
+        }
+    }
+}
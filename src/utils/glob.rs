@@ -0,0 +1,80 @@
+/// rule names with * or ? in them are patterns, esc_* or mysql*_query
+pub fn is_glob(pattern: &str) -> bool {
+    pattern.contains('*') || pattern.contains('?')
+}
+
+/// match a glob, * is any run of characters and ? is any one character
+pub fn glob(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // last star and the text position it is matching up to, for backtracking
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                // let the last star eat one more character
+                Some((sp, st)) => {
+                    p = sp + 1;
+                    t = st + 1;
+                    star = Some((sp, st + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// how specific a name is, exact names beat any pattern and more literal characters win
+pub fn specificity(pattern: &str) -> usize {
+    match is_glob(pattern) {
+        true => pattern.chars().filter(|c| *c != '*' && *c != '?').count(),
+        false => usize::MAX,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stars_and_question_marks() {
+        assert!(glob("esc_*", "esc_html"));
+        assert!(glob("esc_*", "esc_"));
+        assert!(glob("mysql*_query", "mysqli_query"));
+        assert!(glob("mysql*_query", "mysql_query"));
+        assert!(glob("wpdb->get_*", "wpdb->get_results"));
+        assert!(glob("get_?ar", "get_var"));
+        assert!(glob("*", ""));
+        assert!(!glob("esc_*", "sanitize_text_field"));
+        assert!(!glob("get_?ar", "get_ar"));
+        assert!(!glob("mysql*_query", "mysqli_query_x"));
+    }
+
+    #[test]
+    fn backtracks_over_stars() {
+        assert!(glob("*_a_*_b", "x_a_y_a_z_b"));
+        assert!(glob("a*a*a", "aaa"));
+        assert!(!glob("a*a*a", "aa"));
+    }
+
+    #[test]
+    fn exact_names_are_most_specific() {
+        assert!(!is_glob("esc_html"));
+        assert_eq!(specificity("esc_html"), usize::MAX);
+        assert_eq!(specificity("esc_*"), 4);
+        assert_eq!(specificity("get_?ar"), 6);
+        assert!(specificity("esc_html*") > specificity("esc_*"));
+    }
+}
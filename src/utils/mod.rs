@@ -1 +1,3 @@
+pub mod composer;
 pub mod dumper;
+pub mod glob;